@@ -0,0 +1,287 @@
+// what a rule does to the matched processes when it fires, every attempt is audited,
+// including the dry runs and the ones held back by the rate limit
+use crate::alerts::{AlertEvent, AlertKind, Rule};
+use crate::audit::{Audit, AuditEntry};
+use crate::collectors;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::process::{Command, Stdio};
+use std::thread;
+
+// a rule acts on a process at most once per cooldown,
+// all rules together at most MAX_PER_MINUTE times a minute
+const COOLDOWN_MS: u64 = 60 * 1000;
+const MAX_PER_MINUTE: usize = 10;
+const MINUTE_MS: u64 = 60 * 1000;
+// the shell command gets the matched process in this variable
+const PID_VARIABLE: &str = "PROCCESSMANAGER_PID";
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Action {
+    Renice { nice: i32 },
+    Signal { signal: String },
+    Kill,
+    Shell { command: String },
+}
+
+impl Action {
+    fn describe(&self) -> String {
+        match self {
+            Action::Renice { nice } => format!("renice to {}", nice),
+            Action::Signal { signal } => format!("send {}", signal),
+            Action::Kill => "kill".to_string(),
+            Action::Shell { command } => format!("run {}", command),
+        }
+    }
+
+    fn needs_process(&self) -> bool {
+        !matches!(self, Action::Shell { .. })
+    }
+
+    fn run(&self, pid: Option<u32>) -> String {
+        let sent = |result: Option<bool>| match result {
+            Some(true) => "done".to_string(),
+            Some(false) => "failed".to_string(),
+            None => "not supported".to_string(),
+        };
+        match (self, pid) {
+            (Action::Renice { nice }, Some(pid)) => sent(collectors::renice_process(pid, *nice)),
+            (Action::Signal { signal }, Some(pid)) => {
+                sent(collectors::signal_process(pid as usize, signal))
+            }
+            (Action::Kill, Some(pid)) => sent(collectors::kill_process(pid as usize)),
+            (Action::Shell { command }, pid) => match shell(command, pid) {
+                Ok(()) => "started".to_string(),
+                Err(err) => err.to_string(),
+            },
+            (_, None) => "no process".to_string(),
+        }
+    }
+}
+
+fn shell(command: &str, pid: Option<u32>) -> std::io::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command).stdin(Stdio::null());
+    if let Some(pid) = pid {
+        shell.env(PID_VARIABLE, pid.to_string());
+    }
+    let mut child = shell.spawn()?;
+    // reaped in the background so the recorder does not wait for it
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+// cooldowns are per rule and process, so every process of a firing rule gets its action
+#[derive(Default)]
+pub struct Actions {
+    recent: VecDeque<u64>,
+    last_by_target: HashMap<(String, Option<u32>), u64>,
+}
+
+impl Actions {
+    // runs the actions of the rules that just fired
+    pub fn handle(&mut self, audit: &Audit, rules: &[Rule], events: &[AlertEvent]) {
+        for event in events.iter().filter(|event| event.kind == AlertKind::Fired) {
+            let Some(rule) = rules.iter().find(|rule| rule.id == event.rule_id) else {
+                continue;
+            };
+            let Some(action) = &rule.action else {
+                continue;
+            };
+            // never the app itself or init
+            let own = std::process::id();
+            let pids: Vec<Option<u32>> = if event.pids.is_empty() {
+                vec![None]
+            } else {
+                event
+                    .pids
+                    .iter()
+                    .filter(|pid| **pid > 1 && **pid != own)
+                    .map(|pid| Some(*pid))
+                    .collect()
+            };
+            for pid in pids {
+                let outcome = if action.needs_process() && pid.is_none() {
+                    "no process matched".to_string()
+                } else if rule.dry_run {
+                    "dry run".to_string()
+                } else if !self.allow(&rule.id, pid, event.timestamp) {
+                    "rate limited".to_string()
+                } else {
+                    action.run(pid)
+                };
+                let _ = audit.append(&AuditEntry {
+                    timestamp: event.timestamp,
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    action: action.describe(),
+                    pid,
+                    dry_run: rule.dry_run,
+                    outcome,
+                });
+            }
+        }
+    }
+
+    fn allow(&mut self, rule_id: &str, pid: Option<u32>, now: u64) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|at| now.saturating_sub(*at) >= MINUTE_MS)
+        {
+            self.recent.pop_front();
+        }
+        let target = (rule_id.to_string(), pid);
+        let cooling = self
+            .last_by_target
+            .get(&target)
+            .is_some_and(|at| now.saturating_sub(*at) < COOLDOWN_MS);
+        if cooling || self.recent.len() >= MAX_PER_MINUTE {
+            return false;
+        }
+        self.recent.push_back(now);
+        self.last_by_target.insert(target, now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::Condition;
+
+    // a fresh audit file per test, tests run in parallel
+    fn audit(name: &str) -> Audit {
+        let path = std::env::temp_dir().join(format!(
+            "proccessmanager-actions-{}-{}.jsonl",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_file(&path);
+        Audit::new(path)
+    }
+
+    fn rule(action: Action, dry_run: bool) -> Rule {
+        Rule {
+            id: "rule".into(),
+            name: "rule".into(),
+            enabled: true,
+            condition: Condition::SwapUsed { above: 0 },
+            for_secs: 0,
+            action: Some(action),
+            dry_run,
+        }
+    }
+
+    fn fired(timestamp: u64, pids: Vec<u32>) -> AlertEvent {
+        AlertEvent {
+            timestamp,
+            rule_id: "rule".into(),
+            rule_name: "rule".into(),
+            kind: AlertKind::Fired,
+            message: String::new(),
+            pids,
+        }
+    }
+
+    // oldest first
+    fn outcomes(audit: &Audit) -> Vec<(Option<u32>, String)> {
+        let mut entries = audit.entries(usize::MAX).unwrap();
+        entries.reverse();
+        entries
+            .into_iter()
+            .map(|entry| (entry.pid, entry.outcome))
+            .collect()
+    }
+
+    #[test]
+    fn init_and_the_app_itself_are_left_alone() {
+        let audit = audit("spared");
+        let rules = [rule(Action::Kill, true)];
+        let own = std::process::id();
+        Actions::default().handle(&audit, &rules, &[fired(0, vec![1, own, 4242])]);
+        assert_eq!(outcomes(&audit), vec![(Some(4242), "dry run".to_string())]);
+    }
+
+    #[test]
+    fn dry_runs_only_audit() {
+        let audit = audit("dry");
+        let rules = [rule(Action::Kill, true)];
+        let mut actions = Actions::default();
+        actions.handle(&audit, &rules, &[fired(0, vec![4242])]);
+        let entries = audit.entries(10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].dry_run);
+        assert_eq!(entries[0].action, "kill");
+        // nor do they use up the rate limit
+        assert!(actions.recent.is_empty());
+    }
+
+    #[test]
+    fn a_process_action_without_a_process_is_not_run() {
+        let audit = audit("no-process");
+        let rules = [rule(Action::Kill, false)];
+        Actions::default().handle(&audit, &rules, &[fired(0, Vec::new())]);
+        assert_eq!(
+            outcomes(&audit),
+            vec![(None, "no process matched".to_string())]
+        );
+    }
+
+    #[test]
+    fn resolved_alerts_do_nothing() {
+        let audit = audit("resolved");
+        let rules = [rule(Action::Kill, false)];
+        let resolved = AlertEvent {
+            kind: AlertKind::Resolved,
+            ..fired(0, vec![4242])
+        };
+        Actions::default().handle(&audit, &rules, &[resolved]);
+        assert!(outcomes(&audit).is_empty());
+    }
+
+    #[test]
+    fn a_target_cools_down_per_rule_and_process() {
+        let audit = audit("cooldown");
+        let rules = [rule(
+            Action::Shell {
+                command: "true".into(),
+            },
+            false,
+        )];
+        let mut actions = Actions::default();
+        actions.handle(&audit, &rules, &[fired(0, vec![4242])]);
+        actions.handle(&audit, &rules, &[fired(COOLDOWN_MS - 1, vec![4242, 4343])]);
+        actions.handle(&audit, &rules, &[fired(COOLDOWN_MS, vec![4242])]);
+        assert_eq!(
+            outcomes(&audit),
+            vec![
+                (Some(4242), "started".to_string()),
+                (Some(4242), "rate limited".to_string()),
+                (Some(4343), "started".to_string()),
+                (Some(4242), "started".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn at_most_max_per_minute_across_rules() {
+        let mut actions = Actions::default();
+        for pid in 0..MAX_PER_MINUTE as u32 {
+            assert!(actions.allow("first", Some(pid), 1000));
+        }
+        assert!(!actions.allow("second", Some(4242), 1000));
+        assert!(!actions.allow("second", Some(4242), MINUTE_MS + 999));
+        // a minute after the first ones they are out of the window
+        assert!(actions.allow("second", Some(4242), MINUTE_MS + 1000));
+    }
+}
@@ -0,0 +1,655 @@
+// headless server for the desktop app on another machine: the same collectors behind
+// the tauri commands, one json request per websocket message, bearer token required
+use crate::collectors;
+use crate::columns::Column;
+use crate::disk_io::{self, DiskIoSampler};
+use crate::disk_usage::DiskScanner;
+use crate::export;
+use crate::exporter::{self, Exporter, ExporterConfig, Render};
+use crate::history::{self, History, Recorder, TimeRange};
+use crate::mounts::DiskSampler;
+use crate::network::NetworkSampler;
+use crate::ports;
+use crate::removable;
+use crate::sampler::SystemSampler;
+use crate::threads::ThreadSampler;
+use clap::Parser;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::error::ProtocolError;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::{header::AUTHORIZATION, StatusCode};
+use tungstenite::{Message, WebSocket};
+
+pub const DEFAULT_PORT: u16 = 7878;
+// how long a connection waits for a request before sending queued events
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const MOUNT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+// a client has this long for the tls and websocket handshake, idle sockets are closed
+// before they get to present a token
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// connections served at once, each one holds a thread
+const MAX_CONNECTIONS: usize = 64;
+
+#[derive(Serialize, Deserialize)]
+pub struct AgentRequest {
+    pub id: u64,
+    pub command: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AgentReply {
+    Response {
+        id: u64,
+        result: Result<Value, Value>,
+    },
+    // what the desktop app would have emitted to its own window
+    Event {
+        event: String,
+        payload: Value,
+    },
+}
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Serves processes, file systems and metrics to a remote process manager"
+)]
+pub struct AgentCli {
+    /// Address to listen on, only this machine can connect until it is given another one
+    #[arg(long, default_value_t = SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT)))]
+    pub listen: SocketAddr,
+    /// PEM certificate chain, serves wss:// instead of ws://
+    #[arg(long, requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key of the certificate
+    #[arg(long, requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+    /// Token clients have to send, a random one is printed when missing
+    #[arg(long, env = "PROCCESSMANAGER_AGENT_TOKEN")]
+    pub token: Option<String>,
+    /// Directory for the recorded history and the exporter config
+    #[arg(long, default_value_os_t = default_data_dir())]
+    pub data_dir: PathBuf,
+}
+
+fn default_data_dir() -> PathBuf {
+    let data = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .unwrap_or_default();
+    data.join("proccessmanager-agent")
+}
+
+pub fn run() -> ExitCode {
+    let cli = AgentCli::parse();
+    let token = match cli.token {
+        Some(token) => token,
+        None => match generate_token() {
+            Ok(token) => {
+                println!("token: {}", token);
+                token
+            }
+            Err(err) => {
+                eprintln!("could not generate a token: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let tls = match (&cli.tls_cert, &cli.tls_key) {
+        (Some(cert), Some(key)) => match tls_config(cert, key) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                eprintln!("{}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => None,
+    };
+    let agent = match Agent::new(token, cli.data_dir) {
+        Ok(agent) => Arc::new(agent),
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let scheme = if tls.is_some() { "wss" } else { "ws" };
+    println!("listening on {}://{}", scheme, cli.listen);
+    if tls.is_none() && !cli.listen.ip().is_loopback() {
+        eprintln!("the token and every reply cross the network unencrypted, see --tls-cert");
+    }
+    match agent.serve(cli.listen, tls) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn tls_config(cert: &Path, key: &Path) -> Result<Arc<ServerConfig>, String> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| format!("{}: {}", cert.display(), err))?;
+    let key =
+        PrivateKeyDer::from_pem_file(key).map_err(|err| format!("{}: {}", key.display(), err))?;
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| err.to_string())?;
+    Ok(Arc::new(config))
+}
+
+// 128 bits from the os random number generator
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|err| err.to_string())?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// a place among the connections, freed when its thread ends, also by a panic
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// the cpu window a connection's processes table polls, dropped with the connection
+struct Client<'a> {
+    system: &'a SystemSampler,
+    id: u64,
+}
+
+impl Drop for Client<'_> {
+    fn drop(&mut self) {
+        self.system.forget(self.id);
+    }
+}
+
+// compares in time independent of where the first difference is
+fn same_token(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[derive(Deserialize)]
+struct NameArgs {
+    name: Option<String>,
+    #[serde(default)]
+    columns: Vec<Column>,
+}
+
+#[derive(Deserialize)]
+struct IdArgs {
+    id: usize,
+}
+
+#[derive(Deserialize)]
+struct SignalArgs {
+    id: usize,
+    signal: String,
+}
+
+#[derive(Deserialize)]
+struct PidArgs {
+    pid: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessArgs {
+    pid: u32,
+    start_time: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSystemArgs {
+    hide_pseudo: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MountPointArgs {
+    mount_point: String,
+}
+
+#[derive(Deserialize)]
+struct DeviceArgs {
+    device: String,
+}
+
+#[derive(Deserialize)]
+struct PathArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct HistoryArgs {
+    range: TimeRange,
+    resolution: u64,
+}
+
+#[derive(Deserialize)]
+struct RangeArgs {
+    range: TimeRange,
+}
+
+#[derive(Deserialize)]
+struct TimestampArgs {
+    timestamp: u64,
+}
+
+#[derive(Deserialize)]
+struct ExporterArgs {
+    config: ExporterConfig,
+}
+
+fn failed(err: impl ToString) -> Value {
+    Value::String(err.to_string())
+}
+
+// args come as the frontend sent them to tauri, so with camelCase keys
+fn parse<T: DeserializeOwned>(args: Value) -> Result<T, Value> {
+    let args = if args.is_null() {
+        Value::Object(Default::default())
+    } else {
+        args
+    };
+    serde_json::from_value(args).map_err(failed)
+}
+
+fn reply<T: Serialize>(value: T) -> Result<Value, Value> {
+    serde_json::to_value(value).map_err(failed)
+}
+
+pub struct Agent {
+    token: String,
+    data_dir: PathBuf,
+    system: SystemSampler,
+    threads: ThreadSampler,
+    networks: NetworkSampler,
+    disk_io: DiskIoSampler,
+    disks: DiskSampler,
+    scanner: DiskScanner,
+    // recorded here like the desktop app does for its own machine
+    history: History,
+    exporter: Exporter,
+    subscribers: Mutex<Vec<Sender<AgentReply>>>,
+    clients: AtomicU64,
+}
+
+impl Agent {
+    pub fn new(token: String, data_dir: PathBuf) -> io::Result<Self> {
+        Ok(Agent {
+            token,
+            history: History::open(data_dir.join("history"))?,
+            exporter: Exporter::default(),
+            data_dir,
+            system: SystemSampler::default(),
+            threads: ThreadSampler::default(),
+            networks: NetworkSampler::default(),
+            disk_io: DiskIoSampler::default(),
+            disks: DiskSampler::default(),
+            scanner: DiskScanner::default(),
+            subscribers: Mutex::new(Vec::new()),
+            clients: AtomicU64::new(0),
+        })
+    }
+
+    pub fn serve(
+        self: Arc<Self>,
+        address: SocketAddr,
+        tls: Option<Arc<ServerConfig>>,
+    ) -> io::Result<()> {
+        let listener = TcpListener::bind(address)?;
+        self.clone().watch_mounts();
+        self.clone().record_history();
+        let config = fs::read(self.exporter_path())
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        if let Err(err) = self.exporter.apply(config, self.exporter_render()) {
+            eprintln!("exporter: {}", err);
+        }
+        let connections = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            // one over the limit is closed right away
+            if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                connections.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            let slot = Slot(connections.clone());
+            let agent = self.clone();
+            let tls = tls.clone();
+            thread::spawn(move || {
+                let _slot = slot;
+                let peer = stream.peer_addr().ok();
+                if let Err(err) = agent.handle(stream, tls) {
+                    eprintln!("{:?}: {}", peer, err);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn watch_mounts(self: Arc<Self>) {
+        thread::spawn(move || loop {
+            thread::sleep(MOUNT_POLL_INTERVAL);
+            if let Some(change) = self.disks.poll_mounts() {
+                self.broadcast("mounts-changed", change);
+            }
+        });
+    }
+
+    fn record_history(self: Arc<Self>) {
+        thread::spawn(move || {
+            let mut recorder = Recorder::default();
+            loop {
+                thread::sleep(history::RECORD_INTERVAL);
+                let record = self.system.with_system(|sys| recorder.sample(sys));
+                let _ = self.history.append(&record);
+            }
+        });
+    }
+
+    fn exporter_path(&self) -> PathBuf {
+        self.data_dir.join("exporter.json")
+    }
+
+    // the exporter reads the same samplers as the requests
+    fn exporter_render(self: &Arc<Self>) -> Render {
+        let agent = self.clone();
+        Arc::new(move || {
+            agent.system.with_system(|sys| {
+                agent.networks.with_networks(|networks, _| {
+                    agent.disks.with_disks(|disks| {
+                        exporter::render(sys, networks, disks, &disk_io::counters())
+                    })
+                })
+            })
+        })
+    }
+
+    fn set_exporter_config(self: &Arc<Self>, config: ExporterConfig) -> io::Result<()> {
+        self.exporter.apply(config, self.exporter_render())?;
+        fs::write(self.exporter_path(), serde_json::to_vec_pretty(&config)?)
+    }
+
+    fn broadcast<T: Serialize>(&self, event: &str, payload: T) {
+        let Ok(payload) = serde_json::to_value(payload) else {
+            return;
+        };
+        let reply = AgentReply::Event {
+            event: event.to_string(),
+            payload,
+        };
+        // closed connections dropped their receiver
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(reply.clone()).is_ok());
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| same_token(token.as_bytes(), self.token.as_bytes()))
+    }
+
+    fn handle(
+        self: Arc<Self>,
+        stream: TcpStream,
+        tls: Option<Arc<ServerConfig>>,
+    ) -> Result<(), String> {
+        stream
+            .set_read_timeout(Some(HANDSHAKE_TIMEOUT))
+            .map_err(|err| err.to_string())?;
+        stream
+            .set_write_timeout(Some(HANDSHAKE_TIMEOUT))
+            .map_err(|err| err.to_string())?;
+        // the same socket under tls, to change its timeout after the handshake
+        let tcp = stream.try_clone().map_err(|err| err.to_string())?;
+        match tls {
+            Some(config) => {
+                let connection = ServerConnection::new(config).map_err(|err| err.to_string())?;
+                self.talk(StreamOwned::new(connection, stream), &tcp)
+            }
+            None => self.talk(stream, &tcp),
+        }
+    }
+
+    // the handshake callback's error type is fixed by tungstenite
+    #[allow(clippy::result_large_err)]
+    fn talk<S: Read + Write>(self: Arc<Self>, stream: S, tcp: &TcpStream) -> Result<(), String> {
+        let mut socket =
+            tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+                if self.authorized(request) {
+                    Ok(response)
+                } else {
+                    let mut rejection = ErrorResponse::new(Some("invalid token".to_string()));
+                    *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+                    Err(rejection)
+                }
+            })
+            .map_err(|err| err.to_string())?;
+        tcp.set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| err.to_string())?;
+
+        let client = Client {
+            system: &self.system,
+            id: self.clients.fetch_add(1, Ordering::Relaxed),
+        };
+        let (sender, events) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(sender);
+        loop {
+            match socket.read() {
+                Ok(Message::Text(text)) => {
+                    let reply = match serde_json::from_str::<AgentRequest>(&text) {
+                        Ok(request) => AgentReply::Response {
+                            id: request.id,
+                            result: self.clone().dispatch(
+                                &request.command,
+                                request.args,
+                                client.id,
+                            ),
+                        },
+                        Err(err) => {
+                            eprintln!("malformed request: {}", err);
+                            continue;
+                        }
+                    };
+                    send(&mut socket, &reply)?;
+                }
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) => {}
+                // a desktop app that quits just drops the connection
+                Err(
+                    tungstenite::Error::ConnectionClosed
+                    | tungstenite::Error::Protocol(ProtocolError::ResetWithoutClosingHandshake),
+                ) => return Ok(()),
+                Err(err) => return Err(err.to_string()),
+            }
+            while let Ok(event) = events.try_recv() {
+                send(&mut socket, &event)?;
+            }
+        }
+    }
+
+    // the commands of the desktop app that make sense on another machine,
+    // answering with the same json the tauri command would, `client` is the connection
+    pub fn dispatch(
+        self: Arc<Self>,
+        command: &str,
+        args: Value,
+        client: u64,
+    ) -> Result<Value, Value> {
+        match command {
+            "os_info" => reply(collectors::system_info()),
+            "process_info" => {
+                let args: NameArgs = parse(args)?;
+                reply(
+                    self.system
+                        .with_trends(&args.columns, client, |sys, trends, window| {
+                            collectors::process_infos_with_trends(
+                                sys,
+                                trends,
+                                window,
+                                args.name,
+                                &args.columns,
+                            )
+                        }),
+                )
+            }
+            "process_history" => {
+                let args: ProcessArgs = parse(args)?;
+                reply(self.system.process_history(args.pid, args.start_time))
+            }
+            "process_threads" => {
+                let args: PidArgs = parse(args)?;
+                reply(self.threads.sample(args.pid))
+            }
+            "kill_process" => {
+                let args: IdArgs = parse(args)?;
+                reply(collectors::kill_process(args.id))
+            }
+            "signal_process" => {
+                let args: SignalArgs = parse(args)?;
+                reply(collectors::signal_process(args.id, &args.signal))
+            }
+            "file_systems" => {
+                let args: FileSystemArgs = parse(args)?;
+                reply(self.disks.with_disks(|disks| {
+                    collectors::disk_infos(disks, args.hide_pseudo.unwrap_or(false))
+                }))
+            }
+            "unmount" => {
+                let args: MountPointArgs = parse(args)?;
+                removable::unmount_removable(&self.disks, Path::new(&args.mount_point))
+                    .map_err(|err| serde_json::to_value(err).unwrap_or_default())
+                    .and_then(reply)
+            }
+            "eject" => {
+                let args: DeviceArgs = parse(args)?;
+                removable::eject_removable(&self.disks, &args.device)
+                    .map_err(|err| serde_json::to_value(err).unwrap_or_default())
+                    .and_then(reply)
+            }
+            "analyze_disk" => {
+                let args: PathArgs = parse(args)?;
+                let agent = self.clone();
+                self.scanner
+                    .start(PathBuf::from(args.path), move |progress| {
+                        agent.broadcast("disk-scan-progress", progress);
+                    });
+                reply(())
+            }
+            "cancel_disk_scan" => {
+                self.scanner.cancel();
+                reply(())
+            }
+            "disk_scan_node" => {
+                let args: PathArgs = parse(args)?;
+                reply(self.scanner.node(Path::new(&args.path)))
+            }
+            "disk_io" => reply(self.disk_io.sample()),
+            "get_metrics" => reply(self.system.with_system(|sys| {
+                self.networks
+                    .with_networks(|networks, _| collectors::metrics(sys, networks))
+            })),
+            "host_summary" => reply(self.system.with_system(|sys| {
+                self.disks
+                    .with_disks(|disks| collectors::summary(sys, disks))
+            })),
+            "network_interfaces" => reply(self.networks.sample()),
+            "history" => {
+                let args: HistoryArgs = parse(args)?;
+                self.history
+                    .query(args.range, args.resolution)
+                    .map_err(failed)
+                    .and_then(reply)
+            }
+            "history_bounds" => self.history.bounds().map_err(failed).and_then(reply),
+            "history_snapshot" => {
+                let args: TimestampArgs = parse(args)?;
+                self.history
+                    .snapshot(args.timestamp)
+                    .map_err(failed)
+                    .and_then(reply)
+            }
+            "history_diff" => {
+                let args: RangeArgs = parse(args)?;
+                self.history
+                    .diff(args.range)
+                    .map_err(failed)
+                    .and_then(reply)
+            }
+            "exporter_config" => reply(self.exporter.config()),
+            "set_exporter_config" => {
+                let args: ExporterArgs = parse(args)?;
+                self.set_exporter_config(args.config)
+                    .map_err(failed)
+                    .and_then(reply)
+            }
+            "listening_ports" => reply(ports::listening_ports()),
+            // raw rows for exports, written to a file on the desktop side
+            "process_rows" => {
+                let args: NameArgs = parse(args)?;
+                reply(
+                    self.system
+                        .with_system(|sys| export::process_rows(sys, args.name.as_deref())),
+                )
+            }
+            "file_system_rows" => {
+                let args: FileSystemArgs = parse(args)?;
+                reply(self.disks.with_disks(|disks| {
+                    export::file_system_rows(disks, args.hide_pseudo.unwrap_or(false))
+                }))
+            }
+            _ => Err(Value::String(format!(
+                "{} is not available on a remote host",
+                command
+            ))),
+        }
+    }
+}
+
+fn send<S: Read + Write, T: Serialize>(
+    socket: &mut WebSocket<S>,
+    message: &T,
+) -> Result<(), String> {
+    let text = serde_json::to_string(message).map_err(|err| err.to_string())?;
+    socket
+        .send(Message::text(text))
+        .map_err(|err| err.to_string())
+}
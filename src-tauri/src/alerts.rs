@@ -0,0 +1,399 @@
+// rules checked against every history record, a rule fires once its condition held for
+// `for_secs` and resolves only once the value dropped clearly below the threshold again
+use crate::actions::Action;
+use crate::collectors::format_memory_size;
+use crate::history::now_millis;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use sysinfo::{Disks, System};
+
+// how far below its threshold a value has to fall to resolve, so values hovering
+// around the threshold do not fire over and over
+const HYSTERESIS: f64 = 0.05;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Condition {
+    // percent of one core, of the busiest process with that name
+    ProcessCpu { name: String, above: f64 },
+    // percent of the file system used
+    MountUsage { mount_point: String, above: f64 },
+    // bytes
+    SwapUsed { above: u64 },
+    ProcessMissing { name: String },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Rule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub condition: Condition,
+    // how long the condition has to hold before the rule fires
+    #[serde(default)]
+    pub for_secs: u64,
+    // run when the rule fires
+    #[serde(default)]
+    pub action: Option<Action>,
+    // only write what the action would have done to the audit trail
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Fired,
+    Resolved,
+}
+
+// kept with the history records
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AlertEvent {
+    pub timestamp: u64,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub kind: AlertKind,
+    pub message: String,
+    // the processes that made the rule fire, if the condition is about processes
+    #[serde(default)]
+    pub pids: Vec<u32>,
+}
+
+#[derive(Serialize)]
+pub struct FiringAlert {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub since: u64,
+    pub message: String,
+}
+
+struct Check {
+    over: bool,
+    cleared: bool,
+    message: String,
+    pids: Vec<u32>,
+}
+
+fn threshold(value: f64, above: f64, message: String) -> Check {
+    Check {
+        over: value > above,
+        cleared: value < above * (1.0 - HYSTERESIS),
+        message,
+        pids: Vec::new(),
+    }
+}
+
+impl Condition {
+    fn check(&self, sys: &System, disks: &Disks) -> Check {
+        match self {
+            Condition::ProcessCpu { name, above } => {
+                let matching: Vec<(u32, f64)> = sys
+                    .processes()
+                    .iter()
+                    .filter(|(_, process)| process.thread_kind().is_none())
+                    .filter(|(_, process)| process.name().to_string_lossy() == name.as_str())
+                    .map(|(pid, process)| (pid.as_u32(), process.cpu_usage() as f64))
+                    .collect();
+                let cpu = matching.iter().map(|(_, cpu)| *cpu).fold(0.0, f64::max);
+                Check {
+                    pids: matching
+                        .iter()
+                        .filter(|(_, cpu)| cpu > above)
+                        .map(|(pid, _)| *pid)
+                        .collect(),
+                    ..threshold(
+                        cpu,
+                        *above,
+                        format!("{} at {:.0}% cpu, limit {:.0}%", name, cpu, above),
+                    )
+                }
+            }
+            Condition::MountUsage { mount_point, above } => {
+                let used = disks
+                    .iter()
+                    .find(|disk| disk.mount_point() == Path::new(mount_point))
+                    .filter(|disk| disk.total_space() > 0)
+                    .map(|disk| {
+                        (disk.total_space() - disk.available_space()) as f64
+                            / disk.total_space() as f64
+                            * 100.0
+                    })
+                    .unwrap_or(0.0);
+                threshold(
+                    used,
+                    *above,
+                    format!("{} {:.0}% full, limit {:.0}%", mount_point, used, above),
+                )
+            }
+            Condition::SwapUsed { above } => {
+                let used = sys.used_swap();
+                threshold(
+                    used as f64,
+                    *above as f64,
+                    format!(
+                        "{} of swap used, limit {}",
+                        format_memory_size(used),
+                        format_memory_size(*above)
+                    ),
+                )
+            }
+            Condition::ProcessMissing { name } => {
+                let running = sys
+                    .processes()
+                    .values()
+                    .any(|process| process.name().to_string_lossy() == name.as_str());
+                Check {
+                    over: !running,
+                    cleared: running,
+                    message: if running {
+                        format!("{} is running again", name)
+                    } else {
+                        format!("{} is not running", name)
+                    },
+                    pids: Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct RuleState {
+    over_since: Option<u64>,
+    firing_since: Option<u64>,
+    message: String,
+}
+
+impl RuleState {
+    // whether the rule fires or resolves with this check
+    fn advance(&mut self, for_secs: u64, check: &Check, now: u64) -> Option<AlertKind> {
+        self.over_since = if check.over {
+            Some(self.over_since.unwrap_or(now))
+        } else {
+            None
+        };
+        let held = self
+            .over_since
+            .is_some_and(|since| now.saturating_sub(since) >= for_secs * 1000);
+        self.message = check.message.clone();
+        match self.firing_since {
+            None if held => {
+                self.firing_since = Some(now);
+                Some(AlertKind::Fired)
+            }
+            Some(_) if check.cleared => {
+                self.firing_since = None;
+                Some(AlertKind::Resolved)
+            }
+            _ => None,
+        }
+    }
+}
+
+// the rules are kept in a json file next to the exporter config
+pub struct Alerts {
+    path: PathBuf,
+    rules: Mutex<Vec<Rule>>,
+    states: Mutex<HashMap<String, RuleState>>,
+}
+
+impl Alerts {
+    pub fn load(path: PathBuf) -> Self {
+        let rules = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Alerts {
+            path,
+            rules: Mutex::new(rules),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn rules(&self) -> Vec<Rule> {
+        self.rules.lock().unwrap().clone()
+    }
+
+    // new rules come without an id, changed ones keep their state
+    pub fn set_rules(&self, mut rules: Vec<Rule>) -> io::Result<()> {
+        let now = now_millis();
+        for (i, rule) in rules.iter_mut().enumerate() {
+            if rule.id.is_empty() {
+                rule.id = format!("{:x}-{}", now, i);
+            }
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&rules)?)?;
+        self.states
+            .lock()
+            .unwrap()
+            .retain(|id, _| rules.iter().any(|rule| &rule.id == id && rule.enabled));
+        *self.rules.lock().unwrap() = rules;
+        Ok(())
+    }
+
+    // checks every enabled rule, returns what fired or resolved since the last call
+    pub fn evaluate(&self, sys: &System, disks: &Disks, now: u64) -> Vec<AlertEvent> {
+        let rules = self.rules.lock().unwrap();
+        let mut states = self.states.lock().unwrap();
+        let mut events = Vec::new();
+        for rule in rules.iter().filter(|rule| rule.enabled) {
+            let check = rule.condition.check(sys, disks);
+            let state = states.entry(rule.id.clone()).or_default();
+            let Some(kind) = state.advance(rule.for_secs, &check, now) else {
+                continue;
+            };
+            events.push(AlertEvent {
+                timestamp: now,
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                kind,
+                message: check.message,
+                pids: check.pids,
+            });
+        }
+        events
+    }
+
+    pub fn firing(&self) -> Vec<FiringAlert> {
+        let rules = self.rules.lock().unwrap();
+        let states = self.states.lock().unwrap();
+        rules
+            .iter()
+            .filter_map(|rule| {
+                let state = states.get(&rule.id)?;
+                Some(FiringAlert {
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    since: state.firing_since?,
+                    message: state.message.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cpu_at(value: f64) -> Check {
+        threshold(value, 90.0, String::new())
+    }
+
+    fn rule(condition: Condition) -> Rule {
+        Rule {
+            id: "rule".into(),
+            name: "rule".into(),
+            enabled: true,
+            condition,
+            for_secs: 0,
+            action: None,
+            dry_run: false,
+        }
+    }
+
+    fn alerts(rules: Vec<Rule>) -> Alerts {
+        Alerts {
+            path: PathBuf::new(),
+            rules: Mutex::new(rules),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn threshold_clears_only_below_the_hysteresis() {
+        let over = cpu_at(91.0);
+        assert!(over.over && !over.cleared);
+        let between = cpu_at(88.0);
+        assert!(!between.over && !between.cleared);
+        let below = cpu_at(85.0);
+        assert!(!below.over && below.cleared);
+    }
+
+    #[test]
+    fn stays_firing_until_cleared() {
+        let mut state = RuleState::default();
+        assert_eq!(state.advance(0, &cpu_at(95.0), 0), Some(AlertKind::Fired));
+        assert_eq!(state.advance(0, &cpu_at(95.0), 1000), None);
+        // dropping under the threshold but not under the hysteresis keeps it firing
+        assert_eq!(state.advance(0, &cpu_at(88.0), 2000), None);
+        assert_eq!(state.advance(0, &cpu_at(91.0), 3000), None);
+        assert_eq!(
+            state.advance(0, &cpu_at(80.0), 4000),
+            Some(AlertKind::Resolved)
+        );
+        assert_eq!(state.advance(0, &cpu_at(80.0), 5000), None);
+        assert_eq!(
+            state.advance(0, &cpu_at(95.0), 6000),
+            Some(AlertKind::Fired)
+        );
+    }
+
+    #[test]
+    fn fires_once_held_for_secs() {
+        let mut state = RuleState::default();
+        assert_eq!(state.advance(10, &cpu_at(95.0), 0), None);
+        assert_eq!(state.advance(10, &cpu_at(95.0), 5000), None);
+        assert_eq!(
+            state.advance(10, &cpu_at(95.0), 10_000),
+            Some(AlertKind::Fired)
+        );
+    }
+
+    #[test]
+    fn dropping_below_restarts_the_hold() {
+        let mut state = RuleState::default();
+        assert_eq!(state.advance(10, &cpu_at(95.0), 0), None);
+        assert_eq!(state.advance(10, &cpu_at(88.0), 5000), None);
+        assert_eq!(state.advance(10, &cpu_at(95.0), 10_000), None);
+        assert_eq!(state.advance(10, &cpu_at(95.0), 15_000), None);
+        assert_eq!(
+            state.advance(10, &cpu_at(95.0), 20_000),
+            Some(AlertKind::Fired)
+        );
+    }
+
+    #[test]
+    fn missing_process_fires_once() {
+        let alerts = alerts(vec![rule(Condition::ProcessMissing {
+            name: "no such process".into(),
+        })]);
+        let (sys, disks) = (System::new(), Disks::new());
+        let events = alerts.evaluate(&sys, &disks, 0);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, AlertKind::Fired);
+        assert_eq!(events[0].message, "no such process is not running");
+        assert!(alerts.evaluate(&sys, &disks, 5000).is_empty());
+        assert_eq!(alerts.firing().len(), 1);
+    }
+
+    #[test]
+    fn disabled_rules_are_not_checked() {
+        let mut missing = rule(Condition::ProcessMissing {
+            name: "no such process".into(),
+        });
+        missing.enabled = false;
+        let alerts = alerts(vec![missing]);
+        assert!(alerts.evaluate(&System::new(), &Disks::new(), 0).is_empty());
+        assert!(alerts.firing().is_empty());
+    }
+
+    #[test]
+    fn unknown_mount_counts_as_empty() {
+        let check = Condition::MountUsage {
+            mount_point: "/no/such/mount".into(),
+            above: 90.0,
+        }
+        .check(&System::new(), &Disks::new());
+        assert!(!check.over && check.cleared);
+    }
+}
@@ -0,0 +1,67 @@
+// append-only json-lines log of what was done to processes without anyone clicking
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AuditEntry {
+    // milliseconds since the epoch
+    pub timestamp: u64,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub action: String,
+    pub pid: Option<u32>,
+    pub dry_run: bool,
+    pub outcome: String,
+}
+
+pub struct Audit {
+    path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl Audit {
+    pub fn new(path: PathBuf) -> Self {
+        Audit {
+            path,
+            file: Mutex::new(None),
+        }
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        file.as_mut().unwrap().write_all(&line)
+    }
+
+    // the newest `limit` entries, newest first
+    pub fn entries(&self, limit: usize) -> io::Result<Vec<AuditEntry>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        let mut entries: Vec<AuditEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect();
+        entries.reverse();
+        entries.truncate(limit);
+        Ok(entries)
+    }
+}
@@ -0,0 +1,322 @@
+use crate::collectors::{self, DiskInfo, ProccessInfo, SysMetrics};
+use crate::network::NetworkSampler;
+use crate::sampler::SystemSampler;
+use crate::tui;
+use clap::error::ErrorKind;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+use sysinfo::Disks;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Process manager, runs the desktop app without a subcommand"
+)]
+pub struct Cli {
+    /// Run the terminal ui instead of the desktop app
+    #[arg(long)]
+    pub tui: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List processes
+    Ps {
+        /// Only processes whose name starts with this
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, value_enum, default_value_t = SortBy::Name)]
+        sort: SortBy,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the busiest processes every interval
+    Top {
+        /// Seconds between updates
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+        /// Stop after this many updates
+        #[arg(long)]
+        count: Option<u64>,
+        /// Number of processes shown
+        #[arg(long, default_value_t = 15)]
+        limit: usize,
+        #[arg(long, value_enum, default_value_t = SortBy::Cpu)]
+        sort: SortBy,
+        /// One json document per line and update
+        #[arg(long)]
+        json: bool,
+    },
+    /// Send a signal to a process
+    Kill {
+        pid: usize,
+        /// HUP, INT, QUIT, KILL, TERM, USR1, USR2, STOP or CONT
+        #[arg(long, short, default_value = "KILL")]
+        signal: String,
+        #[arg(long)]
+        json: bool,
+    },
+    /// List file systems
+    Df {
+        /// Include pseudo file systems
+        #[arg(long, short)]
+        all: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show cpu, memory, swap and network totals
+    Metrics {
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Name,
+    Cpu,
+    Memory,
+}
+
+pub fn sort_processes(processes: &mut [ProccessInfo], sort: SortBy) {
+    match sort {
+        SortBy::Name => processes.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortBy::Cpu => processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu)),
+        SortBy::Memory => processes.sort_by_key(|process| Reverse(process.memory)),
+    }
+}
+
+// the same sampler as the app, given time for a second refresh so cpu usage is set
+fn warm_sampler() -> SystemSampler {
+    let sampler = SystemSampler::default();
+    thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    sampler
+}
+
+// runs a subcommand, None means the desktop app should start instead
+pub fn run() -> Option<ExitCode> {
+    // arguments meant for the desktop app, like a file from the desktop entry or the
+    // -psn_ of macos, start it instead of failing
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err)
+            if matches!(
+                err.kind(),
+                ErrorKind::DisplayHelp | ErrorKind::DisplayVersion
+            ) =>
+        {
+            let _ = err.print();
+            return Some(ExitCode::SUCCESS);
+        }
+        Err(_) => return None,
+    };
+    if cli.tui && cli.command.is_none() {
+        return Some(match tui::run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("{}", err);
+                ExitCode::FAILURE
+            }
+        });
+    }
+    let command = cli.command?;
+    Some(match command {
+        Command::Ps { name, sort, json } => {
+            let mut processes =
+                warm_sampler().with_system(|sys| collectors::process_infos(sys, name));
+            sort_processes(&mut processes, sort);
+            print(processes.as_slice(), json, process_table);
+            ExitCode::SUCCESS
+        }
+        Command::Top {
+            interval,
+            count,
+            limit,
+            sort,
+            json,
+        } => {
+            let sampler = warm_sampler();
+            let mut updates = 0;
+            loop {
+                let mut processes = sampler.with_system(|sys| collectors::process_infos(sys, None));
+                sort_processes(&mut processes, sort);
+                processes.truncate(limit);
+                if json {
+                    println!("{}", serde_json::to_string(&processes).unwrap_or_default());
+                } else {
+                    // clear the screen and move to the top left
+                    print!("\x1b[2J\x1b[H");
+                    println!("{}", process_table(&processes));
+                }
+                updates += 1;
+                if count.is_some_and(|count| updates >= count) {
+                    break;
+                }
+                thread::sleep(Duration::from_secs(interval.max(1)));
+            }
+            ExitCode::SUCCESS
+        }
+        Command::Kill { pid, signal, json } => {
+            let result = collectors::signal_process(pid, &signal);
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "pid": pid, "signal": signal, "sent": result })
+                );
+            } else {
+                match result {
+                    Some(true) => println!("sent {} to {}", signal, pid),
+                    Some(false) => eprintln!("could not send {} to {}", signal, pid),
+                    None => eprintln!("no process {} or unknown signal {}", pid, signal),
+                }
+            }
+            if result == Some(true) {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Command::Df { all, json } => {
+            let disks = Disks::new_with_refreshed_list();
+            print(
+                collectors::disk_infos(&disks, !all).as_slice(),
+                json,
+                disk_table,
+            );
+            ExitCode::SUCCESS
+        }
+        Command::Metrics { json } => {
+            // created before the wait, so the network rates cover it
+            let networks = NetworkSampler::default();
+            let metrics = warm_sampler().with_system(|sys| {
+                networks.with_networks(|networks, _| collectors::metrics(sys, networks))
+            });
+            print(&metrics, json, metrics_table);
+            ExitCode::SUCCESS
+        }
+    })
+}
+
+fn print<T: Serialize + ?Sized>(value: &T, json: bool, table: fn(&T) -> String) {
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(value).unwrap_or_default()
+        );
+    } else {
+        println!("{}", table(value));
+    }
+}
+
+// left aligned columns as wide as their longest cell
+pub fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<String>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = *width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(headers.iter().map(|h| h.to_string()).collect())];
+    lines.extend(rows.into_iter().map(line));
+    lines.join("\n")
+}
+
+fn process_table(processes: &[ProccessInfo]) -> String {
+    table(
+        &[
+            "PID",
+            "NAME",
+            "CPU %",
+            "MEMORY MB",
+            "READ MB",
+            "WRITE MB",
+            "THREADS",
+        ],
+        processes
+            .iter()
+            .map(|process| {
+                vec![
+                    process.id.clone(),
+                    process.name.clone(),
+                    format!("{:.1}", process.cpu),
+                    process.memory.to_string(),
+                    process.disk_read.to_string(),
+                    process.disk_write.to_string(),
+                    process.threads.to_string(),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn disk_table(disks: &[DiskInfo]) -> String {
+    table(
+        &[
+            "DEVICE",
+            "TYPE",
+            "SIZE",
+            "USED",
+            "AVAIL",
+            "USE %",
+            "MOUNTED ON",
+        ],
+        disks
+            .iter()
+            .map(|disk| {
+                vec![
+                    disk.device.clone(),
+                    disk.r#type.clone(),
+                    disk.total.clone(),
+                    disk.used.clone(),
+                    disk.available.clone(),
+                    format!("{:.0}", disk.percent_used),
+                    disk.directory.clone(),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn metrics_table(metrics: &SysMetrics) -> String {
+    let cpus: Vec<String> = metrics
+        .cpus
+        .iter()
+        .map(|cpu| format!("{:.1}", cpu))
+        .collect();
+    table(
+        &["METRIC", "VALUE"],
+        vec![
+            vec!["cpu %".into(), cpus.join(" ")],
+            vec!["memory used".into(), metrics.used_memory.clone()],
+            vec!["memory free".into(), metrics.free_memory.clone()],
+            vec!["memory total".into(), metrics.total_memory.clone()],
+            vec!["swap used".into(), metrics.used_swap.clone()],
+            vec!["swap free".into(), metrics.free_swap.clone()],
+            vec!["swap total".into(), metrics.total_swap.clone()],
+            vec![
+                "network received".into(),
+                collectors::format_memory_size(metrics.total_recived),
+            ],
+            vec![
+                "network transmitted".into(),
+                collectors::format_memory_size(metrics.total_transmited),
+            ],
+        ],
+    )
+}
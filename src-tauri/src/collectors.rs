@@ -0,0 +1,337 @@
+// the data behind the commands, free of tauri so the cli, tui and agent can share it
+use crate::columns::{self, Column, ProcessFields};
+use crate::mounts;
+use crate::sampler::{CpuWindow, Trends};
+use serde::{Deserialize, Serialize};
+use std::env;
+use sysinfo::{Disks, Networks, Pid, Signal, System, Users};
+
+#[derive(Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub name: String,
+}
+
+pub fn system_info() -> SystemInfo {
+    SystemInfo {
+        name: env::consts::OS.to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, PartialOrd)]
+pub struct ProccessInfo {
+    pub id: String,
+    pub name: String,
+    pub cpu: f32,
+    pub memory: u64,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub threads: usize,
+    // seconds since the epoch, tells a reused pid apart
+    #[serde(default)]
+    pub start_time: u64,
+    // the last samples, oldest first, memory in megabytes like `memory`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cpu_trend: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_trend: Vec<u64>,
+    // the columns beyond these, only when asked for
+    #[serde(flatten)]
+    pub fields: ProcessFields,
+}
+
+pub fn process_infos(sys: &System, name: Option<String>) -> Vec<ProccessInfo> {
+    let mut processes: Vec<ProccessInfo> = sys
+        .processes()
+        .iter()
+        .map(|(id, process)| ProccessInfo {
+            id: id.to_string(),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu: process.cpu_usage(),
+            memory: process.memory() / 1024 / 1024,
+            disk_read: process.disk_usage().read_bytes / 1024 / 1024,
+            disk_write: process.disk_usage().written_bytes / 1024 / 1024,
+            threads: process.tasks().map(|tasks| tasks.len() + 1).unwrap_or(1),
+            start_time: process.start_time(),
+            cpu_trend: Vec::new(),
+            memory_trend: Vec::new(),
+            fields: ProcessFields::default(),
+        })
+        .filter(|proc| {
+            if let Some(name) = &name {
+                proc.name.starts_with(name.as_str())
+            } else {
+                true
+            }
+        })
+        .collect();
+    processes.sort_by_key(|e| e.name.clone());
+    processes
+}
+
+// process_infos with the sparkline samples the sampler kept and the requested columns,
+// cpu usage is over the table's own refresh interval once the window has it
+pub fn process_infos_with_trends(
+    sys: &System,
+    trends: &Trends,
+    window: &CpuWindow,
+    name: Option<String>,
+    columns: &[Column],
+) -> Vec<ProccessInfo> {
+    let mut processes = process_infos(sys, name);
+    let users = columns
+        .contains(&Column::User)
+        .then(Users::new_with_refreshed_list);
+    for process in &mut processes {
+        let Some(pid) = process.id.parse::<usize>().ok().map(Pid::from) else {
+            continue;
+        };
+        if let Some(cpu) = window.usage(pid) {
+            process.cpu = cpu;
+        }
+        let trend = trends.get(&pid);
+        if let Some(found) = sys.process(pid) {
+            process.fields = columns::fields(found, trend, users.as_ref(), columns);
+        }
+        let Some(trend) = trend else {
+            continue;
+        };
+        process.cpu_trend = trend.cpu.iter().copied().collect();
+        process.memory_trend = trend
+            .memory
+            .iter()
+            .map(|bytes| bytes / 1024 / 1024)
+            .collect();
+    }
+    processes
+}
+
+pub fn kill_process(id: usize) -> Option<bool> {
+    let mut sys = System::new();
+    sys.refresh_all();
+    let result = sys
+        .processes()
+        .get(&Pid::from(id))
+        .map(|process| process.kill());
+    result
+}
+
+pub fn parse_signal(signal: &str) -> Option<Signal> {
+    let signal = signal.to_ascii_uppercase();
+    Some(match signal.trim_start_matches("SIG") {
+        "HUP" => Signal::Hangup,
+        "INT" => Signal::Interrupt,
+        "QUIT" => Signal::Quit,
+        "KILL" => Signal::Kill,
+        "TERM" => Signal::Term,
+        "USR1" => Signal::User1,
+        "USR2" => Signal::User2,
+        "STOP" => Signal::Stop,
+        "CONT" => Signal::Continue,
+        _ => return None,
+    })
+}
+
+// None when the signal or the process is unknown, or the platform lacks the signal
+pub fn signal_process(id: usize, signal: &str) -> Option<bool> {
+    let signal = parse_signal(signal)?;
+    let mut sys = System::new();
+    sys.refresh_all();
+    sys.processes()
+        .get(&Pid::from(id))
+        .and_then(|process| process.kill_with(signal))
+}
+
+// None where processes have no nice value
+#[cfg(unix)]
+pub fn renice_process(id: u32, nice: i32) -> Option<bool> {
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, id as libc::id_t, nice) };
+    Some(result == 0)
+}
+
+#[cfg(not(unix))]
+pub fn renice_process(_id: u32, _nice: i32) -> Option<bool> {
+    None
+}
+
+pub fn format_memory_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    const TB: f64 = GB * 1024.0;
+
+    let size = bytes as f64;
+    let (value, unit) = if size >= TB {
+        (size / TB, "TB")
+    } else if size >= GB {
+        (size / GB, "GB")
+    } else if size >= MB {
+        (size / MB, "MB")
+    } else if size >= KB {
+        (size / KB, "KB")
+    } else {
+        (size, "bytes")
+    };
+
+    format!("{:.2} {}", value, unit)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DiskInfo {
+    pub device: String,
+    pub directory: String,
+    pub r#type: String,
+    pub total: String,
+    pub available: String,
+    pub used: String,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub inodes_free: u64,
+    pub read_only: bool,
+    pub removable: bool,
+    pub kind: String,
+    pub options: String,
+    pub percent_used: f64,
+}
+
+pub fn disk_infos(disks: &Disks, hide_pseudo: bool) -> Vec<DiskInfo> {
+    let options = mounts::mount_options();
+    let mut file_systems = Vec::new();
+    for disk in disks {
+        if hide_pseudo
+            && mounts::is_pseudo(
+                &disk.file_system().to_string_lossy(),
+                &disk.name().to_string_lossy(),
+                disk.mount_point(),
+            )
+        {
+            continue;
+        }
+        let used = disk.total_space() - disk.available_space();
+        let inodes = mounts::inode_usage(disk.mount_point());
+        let file_system = DiskInfo {
+            device: disk.name().to_string_lossy().to_string(),
+            directory: disk.mount_point().to_string_lossy().to_string(),
+            r#type: disk.file_system().to_string_lossy().to_string(),
+            total: format_memory_size(disk.total_space()),
+            available: format_memory_size(disk.available_space()),
+            used: format_memory_size(used),
+            inodes_total: inodes.as_ref().map_or(0, |i| i.total),
+            inodes_used: inodes.as_ref().map_or(0, |i| i.used),
+            inodes_free: inodes.as_ref().map_or(0, |i| i.free),
+            read_only: disk.is_read_only(),
+            removable: disk.is_removable(),
+            kind: disk.kind().to_string(),
+            options: options.get(disk.mount_point()).cloned().unwrap_or_default(),
+            percent_used: if disk.total_space() > 0 {
+                used as f64 / disk.total_space() as f64 * 100.0
+            } else {
+                0.0
+            },
+        };
+        file_systems.push(file_system);
+    }
+    file_systems
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SysMetrics {
+    pub cpus: Vec<f32>,
+    pub used_memory: String,
+    pub total_memory: String,
+    pub free_memory: String,
+    pub used_swap: String,
+    pub total_swap: String,
+    pub free_swap: String,
+    //network
+    pub recived: u64,
+    pub total_recived: u64,
+    pub transmited: u64,
+    pub total_transmited: u64,
+    // disk
+}
+
+// `network` has to be a kept instance, a fresh one has nothing to compare against
+pub fn metrics(sys: &System, network: &Networks) -> SysMetrics {
+    SysMetrics {
+        cpus: sys.cpus().iter().map(|c| c.cpu_usage()).collect(),
+        used_memory: format_memory_size(sys.used_memory()),
+        total_memory: format_memory_size(sys.total_memory()),
+        free_memory: format_memory_size(sys.free_memory()),
+        used_swap: format_memory_size(sys.used_swap()),
+        total_swap: format_memory_size(sys.total_swap()),
+        free_swap: format_memory_size(sys.free_swap()),
+        recived: network.values().map(|iface| iface.received()).sum(),
+        total_recived: network.values().map(|iface| iface.total_received()).sum(),
+        transmited: network.values().map(|iface| iface.transmitted()).sum(),
+        total_transmited: network
+            .values()
+            .map(|iface| iface.total_transmitted())
+            .sum(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MountUsage {
+    pub mount_point: String,
+    pub percent_used: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TopProcess {
+    pub pid: u32,
+    pub name: String,
+    pub cpu: f32,
+}
+
+// one card of the hosts overview
+#[derive(Serialize, Deserialize)]
+pub struct HostSummary {
+    pub hostname: String,
+    pub cpu: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
+    pub fullest_mount: Option<MountUsage>,
+    pub top_process: Option<TopProcess>,
+}
+
+pub fn summary(sys: &System, disks: &Disks) -> HostSummary {
+    let fullest_mount = disks
+        .iter()
+        .filter(|disk| disk.total_space() > 0)
+        .filter(|disk| {
+            !mounts::is_pseudo(
+                &disk.file_system().to_string_lossy(),
+                &disk.name().to_string_lossy(),
+                disk.mount_point(),
+            )
+        })
+        .map(|disk| MountUsage {
+            mount_point: disk.mount_point().to_string_lossy().into_owned(),
+            percent_used: (disk.total_space() - disk.available_space()) as f64
+                / disk.total_space() as f64
+                * 100.0,
+        })
+        .max_by(|a, b| a.percent_used.total_cmp(&b.percent_used));
+    let top_process = sys
+        .processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind().is_none())
+        .max_by(|(_, a), (_, b)| a.cpu_usage().total_cmp(&b.cpu_usage()))
+        .map(|(pid, process)| TopProcess {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu: process.cpu_usage(),
+        });
+    HostSummary {
+        hostname: System::host_name().unwrap_or_default(),
+        cpu: sys.global_cpu_usage(),
+        memory_used: sys.used_memory(),
+        memory_total: sys.total_memory(),
+        swap_used: sys.used_swap(),
+        swap_total: sys.total_swap(),
+        fullest_mount,
+        top_process,
+    }
+}
@@ -0,0 +1,202 @@
+// the columns of the processes table, the extra fields are only worked out when shown
+use crate::sampler::Trend;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use sysinfo::{Process, Users};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Name,
+    Pid,
+    Cpu,
+    Memory,
+    DiskRead,
+    DiskWrite,
+    Threads,
+    Ppid,
+    User,
+    Status,
+    Nice,
+    StartTime,
+    Runtime,
+    CommandLine,
+    Exe,
+    CpuTime,
+    VirtualMemory,
+    ReadRate,
+    WriteRate,
+    OpenFiles,
+    Cgroup,
+}
+
+// the table as it always was
+pub const DEFAULT_COLUMNS: [Column; 7] = [
+    Column::Name,
+    Column::Pid,
+    Column::Cpu,
+    Column::Memory,
+    Column::DiskRead,
+    Column::DiskWrite,
+    Column::Threads,
+];
+
+// left out of the json unless asked for
+#[derive(Serialize, Deserialize, Default, PartialEq, PartialOrd)]
+pub struct ProcessFields {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ppid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    // seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_time: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    // milliseconds of user and system time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time: Option<u64>,
+    // bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_memory: Option<u64>,
+    // bytes per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_rate: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_rate: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgroup: Option<String>,
+}
+
+// `users` is only needed for the user column
+pub fn fields(
+    process: &Process,
+    trend: Option<&Trend>,
+    users: Option<&Users>,
+    columns: &[Column],
+) -> ProcessFields {
+    let pid = process.pid().as_u32();
+    let stat = columns
+        .iter()
+        .any(|column| matches!(column, Column::Nice | Column::CpuTime))
+        .then(|| proc_stat(pid))
+        .flatten();
+    let mut fields = ProcessFields::default();
+    for column in columns {
+        match column {
+            Column::Ppid => fields.ppid = process.parent().map(|pid| pid.as_u32()),
+            Column::User => {
+                fields.user = process
+                    .user_id()
+                    .and_then(|uid| users?.get_user_by_id(uid))
+                    .map(|user| user.name().to_string())
+            }
+            Column::Status => fields.status = Some(process.status().to_string()),
+            Column::Nice => fields.nice = stat.map(|stat| stat.nice),
+            Column::Runtime => fields.run_time = Some(process.run_time()),
+            Column::CommandLine => {
+                fields.command_line = Some(
+                    process
+                        .cmd()
+                        .iter()
+                        .map(|arg| arg.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            }
+            Column::Exe => {
+                fields.exe = process
+                    .exe()
+                    .map(|path| path.to_string_lossy().into_owned())
+            }
+            Column::CpuTime => fields.cpu_time = stat.map(|stat| stat.cpu_time),
+            Column::VirtualMemory => fields.virtual_memory = Some(process.virtual_memory()),
+            Column::ReadRate => fields.read_rate = trend.map(|trend| trend.read_rate),
+            Column::WriteRate => fields.write_rate = trend.map(|trend| trend.write_rate),
+            Column::OpenFiles => fields.open_files = open_files(pid),
+            Column::Cgroup => fields.cgroup = cgroup(pid),
+            // always there
+            Column::Name
+            | Column::Pid
+            | Column::Cpu
+            | Column::Memory
+            | Column::DiskRead
+            | Column::DiskWrite
+            | Column::Threads
+            | Column::StartTime => {}
+        }
+    }
+    fields
+}
+
+#[derive(Clone, Copy)]
+struct ProcStat {
+    nice: i32,
+    cpu_time: u64,
+}
+
+// sysinfo has neither, so they come from /proc
+#[cfg(target_os = "linux")]
+fn proc_stat(pid: u32) -> Option<ProcStat> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // the name in parentheses may contain spaces
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as u64;
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(ProcStat {
+        nice: fields.get(16)?.parse().ok()?,
+        cpu_time: (utime + stime) * 1000 / ticks,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn proc_stat(_pid: u32) -> Option<ProcStat> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn open_files(pid: u32) -> Option<usize> {
+    Some(fs::read_dir(format!("/proc/{}/fd", pid)).ok()?.count())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_files(_pid: u32) -> Option<usize> {
+    None
+}
+
+// the unified hierarchy's path, or the first controller's on cgroup v1
+#[cfg(target_os = "linux")]
+fn cgroup(pid: u32) -> Option<String> {
+    let content = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let unified = content.lines().find_map(|line| line.strip_prefix("0::"));
+    let first = content.lines().find_map(|line| line.splitn(3, ':').nth(2));
+    unified.or(first).map(str::to_string)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cgroup(_pid: u32) -> Option<String> {
+    None
+}
+
+// the name stays, a table without it makes no sense, and each column is shown once
+pub fn normalized(mut columns: Vec<Column>) -> Vec<Column> {
+    if !columns.contains(&Column::Name) {
+        columns.insert(0, Column::Name);
+    }
+    let mut seen = Vec::new();
+    columns.retain(|column| {
+        let first = !seen.contains(column);
+        seen.push(*column);
+        first
+    });
+    columns
+}
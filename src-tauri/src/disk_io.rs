@@ -0,0 +1,150 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+// /proc/diskstats always counts in 512 byte sectors
+const SECTOR_SIZE: f64 = 512.0;
+
+#[derive(Serialize)]
+pub struct DiskIoInfo {
+    pub device: String,
+    pub reads: f64,
+    pub writes: f64,
+    pub read_bytes: f64,
+    pub write_bytes: f64,
+    pub iops: f64,
+    // percent of time the device had requests in flight
+    pub utilization: f64,
+    // average milliseconds a request spent queued and serviced
+    pub latency: f64,
+    pub queue_depth: f64,
+}
+
+// cumulative counters since boot
+pub struct DiskCounters {
+    pub device: String,
+    pub reads: u64,
+    pub writes: u64,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+#[derive(Clone, Copy, Default)]
+struct DiskStat {
+    reads: u64,
+    sectors_read: u64,
+    ms_reading: u64,
+    writes: u64,
+    sectors_written: u64,
+    ms_writing: u64,
+    ms_doing_io: u64,
+    weighted_ms: u64,
+}
+
+#[derive(Default)]
+pub struct DiskIoSampler {
+    previous: Mutex<Option<(Instant, HashMap<String, DiskStat>)>>,
+}
+
+impl DiskIoSampler {
+    pub fn sample(&self) -> Vec<DiskIoInfo> {
+        let stats = read_diskstats();
+        let now = Instant::now();
+        let mut previous = self.previous.lock().unwrap();
+
+        let mut devices: Vec<DiskIoInfo> = match &*previous {
+            Some((taken, last)) => {
+                let elapsed = now.duration_since(*taken).as_secs_f64().max(0.001);
+                stats
+                    .iter()
+                    .filter_map(|(device, stat)| {
+                        let before = last.get(device)?;
+                        Some(rates(device, before, stat, elapsed))
+                    })
+                    .collect()
+            }
+            None => stats
+                .keys()
+                .map(|device| rates(device, &DiskStat::default(), &DiskStat::default(), 1.0))
+                .collect(),
+        };
+        devices.sort_by(|a, b| a.device.cmp(&b.device));
+
+        *previous = Some((now, stats));
+        devices
+    }
+}
+
+pub fn counters() -> Vec<DiskCounters> {
+    let mut devices: Vec<DiskCounters> = read_diskstats()
+        .into_iter()
+        .map(|(device, stat)| DiskCounters {
+            device,
+            reads: stat.reads,
+            writes: stat.writes,
+            read_bytes: stat.sectors_read * SECTOR_SIZE as u64,
+            write_bytes: stat.sectors_written * SECTOR_SIZE as u64,
+        })
+        .collect();
+    devices.sort_by(|a, b| a.device.cmp(&b.device));
+    devices
+}
+
+fn rates(device: &str, before: &DiskStat, after: &DiskStat, elapsed: f64) -> DiskIoInfo {
+    let delta = |f: fn(&DiskStat) -> u64| f(after).saturating_sub(f(before)) as f64;
+    let reads = delta(|s| s.reads);
+    let writes = delta(|s| s.writes);
+    let completed = reads + writes;
+    DiskIoInfo {
+        device: device.to_string(),
+        reads: reads / elapsed,
+        writes: writes / elapsed,
+        read_bytes: delta(|s| s.sectors_read) * SECTOR_SIZE / elapsed,
+        write_bytes: delta(|s| s.sectors_written) * SECTOR_SIZE / elapsed,
+        iops: completed / elapsed,
+        utilization: (delta(|s| s.ms_doing_io) / (elapsed * 1000.0) * 100.0).min(100.0),
+        latency: if completed > 0.0 {
+            (delta(|s| s.ms_reading) + delta(|s| s.ms_writing)) / completed
+        } else {
+            0.0
+        },
+        queue_depth: delta(|s| s.weighted_ms) / (elapsed * 1000.0),
+    }
+}
+
+// whole block devices only, partitions and loop/ram devices are skipped
+fn read_diskstats() -> HashMap<String, DiskStat> {
+    let Ok(content) = fs::read_to_string("/proc/diskstats") else {
+        return HashMap::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let device = *fields.get(2)?;
+            if device.starts_with("loop")
+                || device.starts_with("ram")
+                || !Path::new("/sys/block").join(device).exists()
+            {
+                return None;
+            }
+            let field = |i: usize| fields.get(i).and_then(|v| v.parse::<u64>().ok());
+            Some((
+                device.to_string(),
+                DiskStat {
+                    reads: field(3)?,
+                    sectors_read: field(5)?,
+                    ms_reading: field(6)?,
+                    writes: field(7)?,
+                    sectors_written: field(9)?,
+                    ms_writing: field(10)?,
+                    ms_doing_io: field(12)?,
+                    weighted_ms: field(13)?,
+                },
+            ))
+        })
+        .collect()
+}
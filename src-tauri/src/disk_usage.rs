@@ -0,0 +1,262 @@
+use rayon::prelude::*;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::fs::{self, Metadata};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// largest files remembered per directory, the rest only count towards the size
+const LARGEST_FILES: usize = 20;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Clone)]
+pub struct ScanProgress {
+    pub path: String,
+    pub files: u64,
+    pub directories: u64,
+    pub bytes: u64,
+    pub finished: bool,
+    pub cancelled: bool,
+}
+
+#[derive(Serialize)]
+pub struct ScanNode {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub files: u64,
+    pub is_dir: bool,
+    pub children: Vec<ScanNode>,
+}
+
+struct Dir {
+    name: String,
+    size: u64,
+    files: u64,
+    dirs: Vec<Dir>,
+    largest_files: Vec<(String, u64)>,
+}
+
+#[derive(Default)]
+struct Counters {
+    files: AtomicU64,
+    directories: AtomicU64,
+    bytes: AtomicU64,
+}
+
+// the tree of the latest scan, a scan that was replaced while it finished must not
+// put its tree over the new one
+#[derive(Default)]
+struct Scan {
+    generation: u64,
+    tree: Option<(PathBuf, Dir)>,
+}
+
+// one scan at a time, starting a new one cancels the previous
+#[derive(Default)]
+pub struct DiskScanner {
+    cancel: Mutex<Option<Arc<AtomicBool>>>,
+    result: Arc<Mutex<Scan>>,
+}
+
+impl DiskScanner {
+    pub fn start<F>(&self, root: PathBuf, on_progress: F)
+    where
+        F: Fn(ScanProgress) + Send + Sync + 'static,
+    {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Some(previous) = self.cancel.lock().unwrap().replace(cancelled.clone()) {
+            previous.store(true, Ordering::Relaxed);
+        }
+        let generation = {
+            let mut scan = self.result.lock().unwrap();
+            scan.generation += 1;
+            scan.tree = None;
+            scan.generation
+        };
+
+        let result = self.result.clone();
+        thread::spawn(move || {
+            let counters = Arc::new(Counters::default());
+            let done = Arc::new(AtomicBool::new(false));
+            let on_progress = Arc::new(on_progress);
+            let progress = {
+                let root = root.clone();
+                let counters = counters.clone();
+                move |finished: bool, cancelled: bool| ScanProgress {
+                    path: root.to_string_lossy().into_owned(),
+                    files: counters.files.load(Ordering::Relaxed),
+                    directories: counters.directories.load(Ordering::Relaxed),
+                    bytes: counters.bytes.load(Ordering::Relaxed),
+                    finished,
+                    cancelled,
+                }
+            };
+
+            let ticker = {
+                let done = done.clone();
+                let on_progress = on_progress.clone();
+                let progress = progress.clone();
+                thread::spawn(move || {
+                    while !done.load(Ordering::Relaxed) {
+                        on_progress(progress(false, false));
+                        thread::sleep(PROGRESS_INTERVAL);
+                    }
+                })
+            };
+
+            let device = fs::symlink_metadata(&root).map(|m| device_id(&m)).ok();
+            let tree = device.map(|device| {
+                let name = root.to_string_lossy().into_owned();
+                scan_dir(&root, name, device, &counters, &cancelled)
+            });
+
+            done.store(true, Ordering::Relaxed);
+            let _ = ticker.join();
+
+            let was_cancelled = cancelled.load(Ordering::Relaxed);
+            if let (Some(tree), false) = (tree, was_cancelled) {
+                let mut scan = result.lock().unwrap();
+                if scan.generation == generation {
+                    scan.tree = Some((root, tree));
+                }
+            }
+            on_progress(progress(true, was_cancelled));
+        });
+    }
+
+    pub fn cancel(&self) {
+        if let Some(cancelled) = self.cancel.lock().unwrap().take() {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    // the directory at `path` with its direct children, largest first
+    pub fn node(&self, path: &Path) -> Option<ScanNode> {
+        let scan = self.result.lock().unwrap();
+        let (root, tree) = scan.tree.as_ref()?;
+        let mut dir = tree;
+        for component in path.strip_prefix(root).ok()?.components() {
+            let Component::Normal(name) = component else {
+                continue;
+            };
+            let name = name.to_string_lossy();
+            dir = dir.dirs.iter().find(|child| child.name == name)?;
+        }
+
+        let mut children: Vec<ScanNode> = dir
+            .dirs
+            .iter()
+            .map(|child| ScanNode {
+                path: path.join(&child.name).to_string_lossy().into_owned(),
+                name: child.name.clone(),
+                size: child.size,
+                files: child.files,
+                is_dir: true,
+                children: Vec::new(),
+            })
+            .chain(dir.largest_files.iter().map(|(name, size)| ScanNode {
+                path: path.join(name).to_string_lossy().into_owned(),
+                name: name.clone(),
+                size: *size,
+                files: 1,
+                is_dir: false,
+                children: Vec::new(),
+            }))
+            .collect();
+        children.sort_by_key(|child| Reverse(child.size));
+
+        Some(ScanNode {
+            path: path.to_string_lossy().into_owned(),
+            name: dir.name.clone(),
+            size: dir.size,
+            files: dir.files,
+            is_dir: true,
+            children,
+        })
+    }
+}
+
+fn scan_dir(
+    path: &Path,
+    name: String,
+    device: u64,
+    counters: &Counters,
+    cancelled: &AtomicBool,
+) -> Dir {
+    let mut dir = Dir {
+        name,
+        size: 0,
+        files: 0,
+        dirs: Vec::new(),
+        largest_files: Vec::new(),
+    };
+    if cancelled.load(Ordering::Relaxed) {
+        return dir;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return dir;
+    };
+
+    let mut subdirs = Vec::new();
+    for entry in entries.flatten() {
+        // DirEntry::metadata does not follow symlinks
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if metadata.is_dir() {
+            if device_id(&metadata) == device {
+                subdirs.push((entry.path(), name));
+            }
+            continue;
+        }
+        let size = disk_size(&metadata);
+        dir.size += size;
+        dir.files += 1;
+        if dir.largest_files.len() < LARGEST_FILES {
+            dir.largest_files.push((name, size));
+        } else if let Some(smallest) = dir.largest_files.iter_mut().min_by_key(|(_, size)| *size) {
+            if smallest.1 < size {
+                *smallest = (name, size);
+            }
+        }
+    }
+    counters.files.fetch_add(dir.files, Ordering::Relaxed);
+    counters.directories.fetch_add(1, Ordering::Relaxed);
+    counters.bytes.fetch_add(dir.size, Ordering::Relaxed);
+
+    dir.dirs = subdirs
+        .into_par_iter()
+        .map(|(path, name)| scan_dir(&path, name, device, counters, cancelled))
+        .collect();
+    for child in &dir.dirs {
+        dir.size += child.size;
+        dir.files += child.files;
+    }
+    dir
+}
+
+#[cfg(unix)]
+fn device_id(metadata: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::dev(metadata)
+}
+
+#[cfg(not(unix))]
+fn device_id(_metadata: &Metadata) -> u64 {
+    0
+}
+
+// allocated size like du, sparse files count what they actually use
+#[cfg(unix)]
+fn disk_size(metadata: &Metadata) -> u64 {
+    std::os::unix::fs::MetadataExt::blocks(metadata) * 512
+}
+
+#[cfg(not(unix))]
+fn disk_size(metadata: &Metadata) -> u64 {
+    metadata.len()
+}
@@ -0,0 +1,141 @@
+use crate::history::TimeRange;
+use crate::mounts;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use sysinfo::{Disks, System};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    JsonLines,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+// what to export, with the same arguments as the command showing it
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportSource {
+    Processes { name: Option<String> },
+    FileSystems { hide_pseudo: bool },
+    History { range: TimeRange, resolution: u64 },
+}
+
+impl ExportSource {
+    pub fn file_stem(&self) -> &'static str {
+        match self {
+            ExportSource::Processes { .. } => "processes",
+            ExportSource::FileSystems { .. } => "file_systems",
+            ExportSource::History { .. } => "history",
+        }
+    }
+}
+
+// rows keep raw numbers instead of the formatted strings the ui shows
+#[derive(Serialize, Deserialize)]
+pub struct ProcessRow {
+    pub pid: u32,
+    pub name: String,
+    pub cpu: f32,
+    pub memory_bytes: u64,
+    pub disk_read_bytes: u64,
+    pub disk_write_bytes: u64,
+    pub threads: usize,
+    pub start_time: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FileSystemRow {
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+    pub used_bytes: u64,
+    pub inodes_total: u64,
+    pub inodes_used: u64,
+    pub read_only: bool,
+    pub removable: bool,
+}
+
+pub fn process_rows(sys: &System, name: Option<&str>) -> Vec<ProcessRow> {
+    let mut rows: Vec<ProcessRow> = sys
+        .processes()
+        .iter()
+        .map(|(pid, process)| ProcessRow {
+            pid: pid.as_u32(),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu: process.cpu_usage(),
+            memory_bytes: process.memory(),
+            disk_read_bytes: process.disk_usage().read_bytes,
+            disk_write_bytes: process.disk_usage().written_bytes,
+            threads: process.tasks().map(|tasks| tasks.len() + 1).unwrap_or(1),
+            start_time: process.start_time(),
+        })
+        .filter(|row| name.is_none_or(|name| row.name.starts_with(name)))
+        .collect();
+    rows.sort_by(|a, b| a.name.cmp(&b.name).then(a.pid.cmp(&b.pid)));
+    rows
+}
+
+pub fn file_system_rows(disks: &Disks, hide_pseudo: bool) -> Vec<FileSystemRow> {
+    disks
+        .iter()
+        .filter(|disk| {
+            !hide_pseudo
+                || !mounts::is_pseudo(
+                    &disk.file_system().to_string_lossy(),
+                    &disk.name().to_string_lossy(),
+                    disk.mount_point(),
+                )
+        })
+        .map(|disk| {
+            let inodes = mounts::inode_usage(disk.mount_point());
+            FileSystemRow {
+                device: disk.name().to_string_lossy().into_owned(),
+                mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                file_system: disk.file_system().to_string_lossy().into_owned(),
+                total_bytes: disk.total_space(),
+                available_bytes: disk.available_space(),
+                used_bytes: disk.total_space() - disk.available_space(),
+                inodes_total: inodes.as_ref().map_or(0, |i| i.total),
+                inodes_used: inodes.as_ref().map_or(0, |i| i.used),
+                read_only: disk.is_read_only(),
+                removable: disk.is_removable(),
+            }
+        })
+        .collect()
+}
+
+pub fn write<T: Serialize>(rows: &[T], format: ExportFormat, path: &Path) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(&mut out);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Json => serde_json::to_writer_pretty(&mut out, rows)?,
+        ExportFormat::JsonLines => {
+            for row in rows {
+                serde_json::to_writer(&mut out, row)?;
+                out.write_all(b"\n")?;
+            }
+        }
+    }
+    out.flush()
+}
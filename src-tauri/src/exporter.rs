@@ -0,0 +1,407 @@
+use crate::disk_io::DiskCounters;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Disks, Networks, System};
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+// processes exposed with their own series, by cpu
+const TOP_PROCESSES: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ExporterConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for ExporterConfig {
+    fn default() -> Self {
+        ExporterConfig {
+            enabled: false,
+            port: 9464,
+        }
+    }
+}
+
+pub type Render = Arc<dyn Fn() -> String + Send + Sync>;
+
+struct Server {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+// prometheus endpoint on localhost, its config is kept with the other settings
+#[derive(Default)]
+pub struct Exporter {
+    config: Mutex<ExporterConfig>,
+    server: Mutex<Option<Server>>,
+}
+
+impl Exporter {
+    pub fn config(&self) -> ExporterConfig {
+        *self.config.lock().unwrap()
+    }
+
+    // stops the running server and starts it again with `config` if enabled,
+    // the config only changes once the port could be bound
+    pub fn apply(&self, config: ExporterConfig, render: Render) -> io::Result<()> {
+        let mut current = self.config.lock().unwrap();
+        let mut server = self.server.lock().unwrap();
+        if let Some(running) = server.take() {
+            running.stop.store(true, Ordering::Relaxed);
+            let _ = running.handle.join();
+        }
+        if config.enabled {
+            match serve(config.port, render.clone()) {
+                Ok(running) => *server = Some(running),
+                Err(err) => {
+                    // keep serving on the old port if the new one is taken,
+                    // if that is gone too the exporter is off
+                    if current.enabled {
+                        *server = serve(current.port, render).ok();
+                        current.enabled = server.is_some();
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        *current = config;
+        Ok(())
+    }
+}
+
+fn serve(port: u16, render: Render) -> io::Result<Server> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    // non-blocking so the thread notices when it is stopped
+    listener.set_nonblocking(true)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = thread::spawn({
+        let stop = stop.clone();
+        move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    // one slow scraper does not hold up the others
+                    Ok((stream, _)) => {
+                        let render = render.clone();
+                        thread::spawn(move || respond(stream, &render));
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL_INTERVAL)
+                    }
+                    Err(_) => thread::sleep(ACCEPT_POLL_INTERVAL),
+                }
+            }
+        }
+    });
+    Ok(Server { stop, handle })
+}
+
+fn respond(mut stream: TcpStream, render: &Render) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next(), parts.next());
+
+    let (status, content_type, body) = match (method, target) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            render(),
+        ),
+        (Some("GET"), Some("/")) => (
+            "200 OK",
+            "text/html; charset=utf-8",
+            "<a href=\"/metrics\">metrics</a>\n".to_string(),
+        ),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+// text exposition format, every family is written with its HELP and TYPE lines
+struct Metrics(String);
+
+impl Metrics {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.0, "# HELP proccessmanager_{} {}", name, help);
+        let _ = writeln!(self.0, "# TYPE proccessmanager_{} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+        let _ = write!(self.0, "proccessmanager_{}", name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+                .collect();
+            let _ = write!(self.0, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.0, " {}", value);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub fn render(
+    sys: &System,
+    networks: &Networks,
+    disks: &Disks,
+    disk_io: &[DiskCounters],
+) -> String {
+    let mut m = Metrics(String::new());
+
+    m.family("cpu_usage_percent", "gauge", "CPU usage per core.");
+    for (i, cpu) in sys.cpus().iter().enumerate() {
+        m.sample(
+            "cpu_usage_percent",
+            &[("cpu", &i.to_string())],
+            cpu.cpu_usage(),
+        );
+    }
+    m.family(
+        "cpu_usage_total_percent",
+        "gauge",
+        "CPU usage over all cores.",
+    );
+    m.sample("cpu_usage_total_percent", &[], sys.global_cpu_usage());
+
+    m.family("memory_total_bytes", "gauge", "Total memory.");
+    m.sample("memory_total_bytes", &[], sys.total_memory());
+    m.family("memory_used_bytes", "gauge", "Used memory.");
+    m.sample("memory_used_bytes", &[], sys.used_memory());
+    m.family("memory_available_bytes", "gauge", "Available memory.");
+    m.sample("memory_available_bytes", &[], sys.available_memory());
+    m.family("swap_total_bytes", "gauge", "Total swap.");
+    m.sample("swap_total_bytes", &[], sys.total_swap());
+    m.family("swap_used_bytes", "gauge", "Used swap.");
+    m.sample("swap_used_bytes", &[], sys.used_swap());
+
+    let mut interfaces: Vec<_> = networks.iter().collect();
+    interfaces.sort_by(|a, b| a.0.cmp(b.0));
+    m.family(
+        "network_received_bytes_total",
+        "counter",
+        "Bytes received per interface.",
+    );
+    for (name, data) in &interfaces {
+        m.sample(
+            "network_received_bytes_total",
+            &[("interface", name)],
+            data.total_received(),
+        );
+    }
+    m.family(
+        "network_transmitted_bytes_total",
+        "counter",
+        "Bytes transmitted per interface.",
+    );
+    for (name, data) in &interfaces {
+        m.sample(
+            "network_transmitted_bytes_total",
+            &[("interface", name)],
+            data.total_transmitted(),
+        );
+    }
+
+    m.family("filesystem_size_bytes", "gauge", "File system size.");
+    for disk in disks {
+        let device = disk.name().to_string_lossy();
+        let mount_point = disk.mount_point().to_string_lossy();
+        m.sample(
+            "filesystem_size_bytes",
+            &[("device", &device), ("mount_point", &mount_point)],
+            disk.total_space(),
+        );
+    }
+    m.family(
+        "filesystem_available_bytes",
+        "gauge",
+        "File system space available.",
+    );
+    for disk in disks {
+        let device = disk.name().to_string_lossy();
+        let mount_point = disk.mount_point().to_string_lossy();
+        m.sample(
+            "filesystem_available_bytes",
+            &[("device", &device), ("mount_point", &mount_point)],
+            disk.available_space(),
+        );
+    }
+
+    m.family(
+        "disk_read_bytes_total",
+        "counter",
+        "Bytes read per block device.",
+    );
+    for disk in disk_io {
+        m.sample(
+            "disk_read_bytes_total",
+            &[("device", &disk.device)],
+            disk.read_bytes,
+        );
+    }
+    m.family(
+        "disk_written_bytes_total",
+        "counter",
+        "Bytes written per block device.",
+    );
+    for disk in disk_io {
+        m.sample(
+            "disk_written_bytes_total",
+            &[("device", &disk.device)],
+            disk.write_bytes,
+        );
+    }
+    m.family(
+        "disk_reads_total",
+        "counter",
+        "Reads completed per block device.",
+    );
+    for disk in disk_io {
+        m.sample("disk_reads_total", &[("device", &disk.device)], disk.reads);
+    }
+    m.family(
+        "disk_writes_total",
+        "counter",
+        "Writes completed per block device.",
+    );
+    for disk in disk_io {
+        m.sample(
+            "disk_writes_total",
+            &[("device", &disk.device)],
+            disk.writes,
+        );
+    }
+
+    let mut processes: Vec<_> = sys
+        .processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind().is_none())
+        .collect();
+    processes.sort_by(|a, b| b.1.cpu_usage().total_cmp(&a.1.cpu_usage()));
+    processes.truncate(TOP_PROCESSES);
+    let labels: Vec<(String, String)> = processes
+        .iter()
+        .map(|(pid, process)| {
+            (
+                pid.to_string(),
+                process.name().to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    m.family(
+        "process_cpu_usage_percent",
+        "gauge",
+        "CPU usage of the busiest processes.",
+    );
+    for ((pid, name), (_, process)) in labels.iter().zip(&processes) {
+        m.sample(
+            "process_cpu_usage_percent",
+            &[("pid", pid), ("name", name)],
+            process.cpu_usage(),
+        );
+    }
+    m.family(
+        "process_memory_bytes",
+        "gauge",
+        "Resident memory of the busiest processes.",
+    );
+    for ((pid, name), (_, process)) in labels.iter().zip(&processes) {
+        m.sample(
+            "process_memory_bytes",
+            &[("pid", pid), ("name", name)],
+            process.memory(),
+        );
+    }
+
+    m.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn render() -> Render {
+        Arc::new(String::new)
+    }
+
+    // a port nothing listens on, as long as no one else takes it in between
+    fn free_port() -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    #[test]
+    fn a_taken_port_leaves_the_exporter_off() {
+        let taken = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let exporter = Exporter::default();
+        let config = ExporterConfig {
+            enabled: true,
+            port,
+        };
+        assert!(exporter.apply(config, render()).is_err());
+        assert!(!exporter.config().enabled);
+    }
+
+    #[test]
+    fn a_taken_port_keeps_the_old_one() {
+        let old = ExporterConfig {
+            enabled: true,
+            port: free_port(),
+        };
+        let exporter = Exporter::default();
+        exporter.apply(old, render()).unwrap();
+        let taken = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let new = ExporterConfig {
+            enabled: true,
+            port: taken.local_addr().unwrap().port(),
+        };
+        assert!(exporter.apply(new, render()).is_err());
+        assert!(exporter.config() == old);
+        assert!(TcpStream::connect((Ipv4Addr::LOCALHOST, old.port)).is_ok());
+    }
+
+    #[test]
+    fn a_slow_scraper_does_not_hold_up_the_others() {
+        let config = ExporterConfig {
+            enabled: true,
+            port: free_port(),
+        };
+        let exporter = Exporter::default();
+        exporter
+            .apply(config, Arc::new(|| "up 1\n".into()))
+            .unwrap();
+        // connected but never sends its request
+        let _slow = TcpStream::connect((Ipv4Addr::LOCALHOST, config.port)).unwrap();
+        thread::sleep(ACCEPT_POLL_INTERVAL * 2);
+        let mut scrape = TcpStream::connect((Ipv4Addr::LOCALHOST, config.port)).unwrap();
+        scrape.set_read_timeout(Some(READ_TIMEOUT / 2)).unwrap();
+        scrape.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        scrape.read_to_string(&mut response).unwrap();
+        assert!(response.ends_with("up 1\n"));
+    }
+}
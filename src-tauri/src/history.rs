@@ -0,0 +1,591 @@
+use crate::alerts::AlertEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use sysinfo::{Networks, System};
+
+pub const RECORD_INTERVAL: Duration = Duration::from_secs(5);
+// processes kept per record, taken by cpu and by memory
+const TOP_PROCESSES: usize = 10;
+const SEGMENT_BYTES: u64 = 1024 * 1024;
+const MAX_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const SEGMENT_PREFIX: &str = "history-";
+const SEGMENT_SUFFIX: &str = ".jsonl";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub name: String,
+    pub cpu: f32,
+    // bytes
+    pub memory: u64,
+    // seconds since the epoch, tells a reused pid apart
+    pub start_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProcessRef {
+    pub pid: u32,
+    pub name: String,
+    pub start_time: u64,
+}
+
+// one line of a segment file
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Record {
+    // milliseconds since the epoch
+    pub timestamp: u64,
+    pub cpu: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub swap_used: u64,
+    // bytes per second over the record interval
+    pub received: u64,
+    pub transmitted: u64,
+    pub processes: Vec<ProcessSample>,
+    // every process that started or ended since the previous record, not just the top ones
+    #[serde(default)]
+    pub started: Vec<ProcessRef>,
+    #[serde(default)]
+    pub ended: Vec<ProcessRef>,
+    // alerts that fired or resolved with this record
+    #[serde(default)]
+    pub alerts: Vec<AlertEvent>,
+}
+
+#[derive(Serialize)]
+pub struct ProcessDiff {
+    pub started: Vec<ProcessRef>,
+    pub ended: Vec<ProcessRef>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct TimeRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+// averaged over one `resolution` wide bucket
+#[derive(Serialize, Deserialize)]
+pub struct MetricPoint {
+    pub timestamp: u64,
+    pub cpu: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub swap_used: u64,
+    pub received: u64,
+    pub transmitted: u64,
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+// turns the shared System into records, network rates are over the record interval
+pub struct Recorder {
+    networks: Networks,
+    last: Instant,
+    // processes seen by the previous sample, by pid and start time
+    known: Option<HashMap<(u32, u64), String>>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder {
+            networks: Networks::new_with_refreshed_list(),
+            last: Instant::now(),
+            known: None,
+        }
+    }
+}
+
+impl Recorder {
+    pub fn sample(&mut self, sys: &System) -> Record {
+        self.networks.refresh(true);
+        let elapsed = self.last.elapsed().as_secs_f64().max(0.001);
+        self.last = Instant::now();
+
+        let mut processes: Vec<ProcessSample> = sys
+            .processes()
+            .iter()
+            // threads are listed as processes on linux
+            .filter(|(_, process)| process.thread_kind().is_none())
+            .map(|(pid, process)| ProcessSample {
+                pid: pid.as_u32(),
+                name: process.name().to_string_lossy().into_owned(),
+                cpu: process.cpu_usage(),
+                memory: process.memory(),
+                start_time: process.start_time(),
+            })
+            .collect();
+
+        let current: HashMap<(u32, u64), String> = processes
+            .iter()
+            .map(|process| ((process.pid, process.start_time), process.name.clone()))
+            .collect();
+        let (started, ended) = match &self.known {
+            Some(known) => (changed(&current, known), changed(known, &current)),
+            None => (Vec::new(), Vec::new()),
+        };
+        self.known = Some(current);
+
+        processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+        let mut top: Vec<ProcessSample> = processes
+            .drain(..TOP_PROCESSES.min(processes.len()))
+            .collect();
+        processes.sort_by_key(|process| std::cmp::Reverse(process.memory));
+        top.extend(processes.into_iter().take(TOP_PROCESSES));
+
+        let received: u64 = self.networks.values().map(|data| data.received()).sum();
+        let transmitted: u64 = self.networks.values().map(|data| data.transmitted()).sum();
+
+        Record {
+            timestamp: now_millis(),
+            cpu: sys.global_cpu_usage(),
+            memory_used: sys.used_memory(),
+            memory_total: sys.total_memory(),
+            swap_used: sys.used_swap(),
+            received: (received as f64 / elapsed) as u64,
+            transmitted: (transmitted as f64 / elapsed) as u64,
+            processes: top,
+            started,
+            ended,
+            alerts: Vec::new(),
+        }
+    }
+}
+
+// processes in `a` that are missing from `b`
+fn changed(a: &HashMap<(u32, u64), String>, b: &HashMap<(u32, u64), String>) -> Vec<ProcessRef> {
+    a.iter()
+        .filter(|(key, _)| !b.contains_key(key))
+        .map(|((pid, start_time), name)| ProcessRef {
+            pid: *pid,
+            name: name.clone(),
+            start_time: *start_time,
+        })
+        .collect()
+}
+
+// records are appended to json-lines segments named after their first timestamp,
+// the oldest segments are dropped once the store is too big or too old
+pub struct History {
+    dir: PathBuf,
+    current: Mutex<Option<(PathBuf, File)>>,
+    segment_bytes: u64,
+    max_bytes: u64,
+}
+
+impl History {
+    pub fn open(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(History {
+            dir,
+            current: Mutex::new(None),
+            segment_bytes: SEGMENT_BYTES,
+            max_bytes: MAX_BYTES,
+        })
+    }
+
+    pub fn append(&self, record: &Record) -> io::Result<()> {
+        let mut current = self.current.lock().unwrap();
+        let full = match &*current {
+            Some((_, file)) => file.metadata()?.len() >= self.segment_bytes,
+            None => true,
+        };
+        if full {
+            let path = self.dir.join(format!(
+                "{}{}{}",
+                SEGMENT_PREFIX, record.timestamp, SEGMENT_SUFFIX
+            ));
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            *current = Some((path, file));
+            self.prune(record.timestamp)?;
+        }
+        let (_, file) = current.as_mut().unwrap();
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line)
+    }
+
+    // segments sorted by the timestamp of their first record
+    fn segments(&self) -> io::Result<Vec<(u64, PathBuf)>> {
+        let mut segments: Vec<(u64, PathBuf)> = fs::read_dir(&self.dir)?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                let start = name
+                    .to_str()?
+                    .strip_prefix(SEGMENT_PREFIX)?
+                    .strip_suffix(SEGMENT_SUFFIX)?
+                    .parse()
+                    .ok()?;
+                Some((start, entry.path()))
+            })
+            .collect();
+        segments.sort_by_key(|(start, _)| *start);
+        Ok(segments)
+    }
+
+    // drops whole segments, never the newest one
+    fn prune(&self, now: u64) -> io::Result<()> {
+        let segments = self.segments()?;
+        let oldest_kept = now.saturating_sub(MAX_AGE.as_millis() as u64);
+        let mut total: u64 = segments
+            .iter()
+            .map(|(_, path)| fs::metadata(path).map_or(0, |m| m.len()))
+            .sum();
+        let prunable = segments.len().saturating_sub(1);
+        for (i, (_, path)) in segments.iter().enumerate().take(prunable) {
+            // a segment ends where the next one starts
+            let expired = segments[i + 1].0 < oldest_kept;
+            if !expired && total <= self.max_bytes {
+                break;
+            }
+            total -= fs::metadata(path).map_or(0, |m| m.len());
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    // every record within `range`, oldest first
+    pub fn records(&self, range: TimeRange) -> io::Result<Vec<Record>> {
+        let segments = self.segments()?;
+        let mut records = Vec::new();
+        for (i, (start, path)) in segments.iter().enumerate() {
+            let end = segments.get(i + 1).map_or(u64::MAX, |(next, _)| *next);
+            if *start > range.end || end < range.start {
+                continue;
+            }
+            let Ok(segment) = read_segment(path) else {
+                continue;
+            };
+            records.extend(
+                segment.into_iter().filter(|record| {
+                    record.timestamp >= range.start && record.timestamp <= range.end
+                }),
+            );
+        }
+        Ok(records)
+    }
+
+    // timestamps of the oldest and newest record
+    pub fn bounds(&self) -> io::Result<Option<TimeRange>> {
+        let segments = self.segments()?;
+        let (Some((first, _)), Some((_, last))) = (segments.first(), segments.last()) else {
+            return Ok(None);
+        };
+        let end = read_segment(last)?
+            .last()
+            .map_or(*first, |record| record.timestamp);
+        Ok(Some(TimeRange { start: *first, end }))
+    }
+
+    // the last record at or before `timestamp`, or the oldest one if there is none
+    pub fn snapshot(&self, timestamp: u64) -> io::Result<Option<Record>> {
+        let segments = self.segments()?;
+        let index = segments
+            .iter()
+            .rposition(|(start, _)| *start <= timestamp)
+            .unwrap_or(0);
+        let Some((_, path)) = segments.get(index) else {
+            return Ok(None);
+        };
+        let mut records = read_segment(path)?;
+        let found = records
+            .iter()
+            .rposition(|record| record.timestamp <= timestamp)
+            .unwrap_or(0);
+        Ok((found < records.len()).then(|| records.swap_remove(found)))
+    }
+
+    // alerts that fired or resolved within `range`, oldest first
+    pub fn alerts(&self, range: TimeRange) -> io::Result<Vec<AlertEvent>> {
+        Ok(self
+            .records(range)?
+            .into_iter()
+            .flat_map(|record| record.alerts)
+            .collect())
+    }
+
+    // processes that started or ended after `range.start` up to `range.end`
+    pub fn diff(&self, range: TimeRange) -> io::Result<ProcessDiff> {
+        let mut diff = ProcessDiff {
+            started: Vec::new(),
+            ended: Vec::new(),
+        };
+        for record in self.records(range)? {
+            if record.timestamp > range.start {
+                diff.started.extend(record.started);
+                diff.ended.extend(record.ended);
+            }
+        }
+        Ok(diff)
+    }
+
+    // metrics within `range`, averaged into buckets of `resolution` milliseconds
+    pub fn query(&self, range: TimeRange, resolution: u64) -> io::Result<Vec<MetricPoint>> {
+        let resolution = resolution.max(1);
+        let mut points: Vec<MetricPoint> = Vec::new();
+        let mut count = 0u64;
+        for record in self.records(range)? {
+            let bucket = record.timestamp - record.timestamp % resolution;
+            match points.last_mut() {
+                Some(point) if point.timestamp == bucket => {
+                    count += 1;
+                    let average = |sum: u64, value: u64| (sum * (count - 1) + value) / count;
+                    point.cpu = (point.cpu * (count - 1) as f32 + record.cpu) / count as f32;
+                    point.memory_used = average(point.memory_used, record.memory_used);
+                    point.memory_total = record.memory_total;
+                    point.swap_used = average(point.swap_used, record.swap_used);
+                    point.received = average(point.received, record.received);
+                    point.transmitted = average(point.transmitted, record.transmitted);
+                }
+                _ => {
+                    count = 1;
+                    points.push(MetricPoint {
+                        timestamp: bucket,
+                        cpu: record.cpu,
+                        memory_used: record.memory_used,
+                        memory_total: record.memory_total,
+                        swap_used: record.swap_used,
+                        received: record.received,
+                        transmitted: record.transmitted,
+                    });
+                }
+            }
+        }
+        Ok(points)
+    }
+}
+
+// a torn last line after a crash is skipped
+fn read_segment(path: &Path) -> io::Result<Vec<Record>> {
+    let file = File::open(path)?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Record>(&line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: TimeRange = TimeRange {
+        start: 0,
+        end: u64::MAX,
+    };
+
+    // a fresh directory per test, tests run in parallel
+    fn history(name: &str, segment_bytes: u64, max_bytes: u64) -> History {
+        let dir = std::env::temp_dir().join(format!(
+            "proccessmanager-history-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        History {
+            segment_bytes,
+            max_bytes,
+            ..History::open(dir).unwrap()
+        }
+    }
+
+    fn record(timestamp: u64) -> Record {
+        Record {
+            timestamp,
+            cpu: 0.0,
+            memory_used: 0,
+            memory_total: 0,
+            swap_used: 0,
+            received: 0,
+            transmitted: 0,
+            processes: Vec::new(),
+            started: Vec::new(),
+            ended: Vec::new(),
+            alerts: Vec::new(),
+        }
+    }
+
+    fn timestamps(records: Vec<Record>) -> Vec<u64> {
+        records.iter().map(|record| record.timestamp).collect()
+    }
+
+    #[test]
+    fn reads_across_segments() {
+        // every record starts a segment of its own
+        let history = history("segments", 1, MAX_BYTES);
+        for timestamp in [1000, 2000, 3000] {
+            history.append(&record(timestamp)).unwrap();
+        }
+        assert_eq!(history.segments().unwrap().len(), 3);
+        assert_eq!(
+            timestamps(history.records(ALL).unwrap()),
+            vec![1000, 2000, 3000]
+        );
+        let middle = TimeRange {
+            start: 1500,
+            end: 2500,
+        };
+        assert_eq!(timestamps(history.records(middle).unwrap()), vec![2000]);
+        let bounds = history.bounds().unwrap().unwrap();
+        assert_eq!((bounds.start, bounds.end), (1000, 3000));
+    }
+
+    #[test]
+    fn fills_a_segment_before_rolling_over() {
+        let history = history("rollover", SEGMENT_BYTES, MAX_BYTES);
+        for timestamp in [1000, 2000, 3000] {
+            history.append(&record(timestamp)).unwrap();
+        }
+        assert_eq!(history.segments().unwrap().len(), 1);
+        assert_eq!(history.records(ALL).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn an_empty_history_has_no_bounds() {
+        let history = history("empty", SEGMENT_BYTES, MAX_BYTES);
+        assert!(history.bounds().unwrap().is_none());
+        assert!(history.snapshot(1000).unwrap().is_none());
+    }
+
+    #[test]
+    fn prunes_segments_older_than_max_age() {
+        let history = history("age", 1, MAX_BYTES);
+        let now = MAX_AGE.as_millis() as u64 + 2000;
+        for timestamp in [0, 1000, now] {
+            history.append(&record(timestamp)).unwrap();
+        }
+        // the first segment ended before the oldest kept time, the second one after it
+        assert_eq!(timestamps(history.records(ALL).unwrap()), vec![1000, now]);
+    }
+
+    #[test]
+    fn prunes_the_oldest_segments_beyond_max_bytes() {
+        let line = serde_json::to_vec(&record(1000)).unwrap().len() as u64 + 1;
+        let history = history("size", 1, 2 * line);
+        for timestamp in [1000, 2000, 3000, 4000, 5000] {
+            history.append(&record(timestamp)).unwrap();
+        }
+        // pruned when the newest segment was still empty
+        assert_eq!(
+            timestamps(history.records(ALL).unwrap()),
+            vec![3000, 4000, 5000]
+        );
+    }
+
+    fn process(pid: u32, start_time: u64) -> ProcessRef {
+        ProcessRef {
+            pid,
+            name: format!("process {}", pid),
+            start_time,
+        }
+    }
+
+    fn keys(processes: &[ProcessRef]) -> Vec<(u32, u64)> {
+        let mut keys: Vec<(u32, u64)> = processes
+            .iter()
+            .map(|process| (process.pid, process.start_time))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn query_averages_into_buckets() {
+        let history = history("query", SEGMENT_BYTES, MAX_BYTES);
+        for (timestamp, cpu, memory) in [(0, 10.0, 100), (1000, 20.0, 200), (2000, 30.0, 300)] {
+            history
+                .append(&Record {
+                    cpu,
+                    memory_used: memory,
+                    memory_total: 1000,
+                    received: memory * 2,
+                    ..record(timestamp)
+                })
+                .unwrap();
+        }
+        let points = history.query(ALL, 2000).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, 0);
+        assert_eq!(points[0].cpu, 15.0);
+        assert_eq!(points[0].memory_used, 150);
+        assert_eq!(points[0].received, 300);
+        assert_eq!(points[1].timestamp, 2000);
+        assert_eq!(points[1].cpu, 30.0);
+        assert_eq!(points[1].memory_total, 1000);
+        // a resolution finer than the records keeps every one of them
+        assert_eq!(history.query(ALL, 1).unwrap().len(), 3);
+        let last = TimeRange {
+            start: 1500,
+            end: u64::MAX,
+        };
+        assert_eq!(history.query(last, 2000).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn snapshot_takes_the_last_record_at_or_before() {
+        // across segments, the lookup starts at the segment holding the timestamp
+        let history = history("snapshot", 1, MAX_BYTES);
+        for timestamp in [1000, 2000, 3000] {
+            history.append(&record(timestamp)).unwrap();
+        }
+        let at = |timestamp| history.snapshot(timestamp).unwrap().unwrap().timestamp;
+        assert_eq!(at(2000), 2000);
+        assert_eq!(at(2999), 2000);
+        assert_eq!(at(10_000), 3000);
+        // before the history starts it is the oldest record
+        assert_eq!(at(500), 1000);
+    }
+
+    #[test]
+    fn diff_collects_what_started_and_ended_after_the_start() {
+        let history = history("diff", SEGMENT_BYTES, MAX_BYTES);
+        let records = [
+            (1000, vec![process(10, 1)], vec![]),
+            (2000, vec![process(11, 2)], vec![process(10, 1)]),
+            (3000, vec![], vec![process(12, 0)]),
+        ];
+        for (timestamp, started, ended) in records {
+            history
+                .append(&Record {
+                    started,
+                    ended,
+                    ..record(timestamp)
+                })
+                .unwrap();
+        }
+        let diff = history
+            .diff(TimeRange {
+                start: 1000,
+                end: 3000,
+            })
+            .unwrap();
+        // what the record at the start itself saw happened before it
+        assert_eq!(keys(&diff.started), vec![(11, 2)]);
+        assert_eq!(keys(&diff.ended), vec![(10, 1), (12, 0)]);
+    }
+
+    #[test]
+    fn a_reused_pid_ended_and_started() {
+        let named = |processes: &[ProcessRef]| -> HashMap<(u32, u64), String> {
+            processes
+                .iter()
+                .map(|process| ((process.pid, process.start_time), process.name.clone()))
+                .collect()
+        };
+        let before = named(&[process(10, 1), process(11, 1)]);
+        let after = named(&[process(10, 5), process(11, 1), process(12, 5)]);
+        assert_eq!(keys(&changed(&after, &before)), vec![(10, 5), (12, 5)]);
+        assert_eq!(keys(&changed(&before, &after)), vec![(10, 1)]);
+    }
+}
@@ -0,0 +1,356 @@
+// remote agents the desktop app knows about and which machine the pages show,
+// kept in a json file next to the exporter config
+use crate::collectors::HostSummary;
+use crate::history::now_millis;
+use crate::remote::{EventSink, RemoteClient, RequestError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Host {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub token: String,
+}
+
+// what the ui gets to see, the token stays in the backend
+#[derive(Serialize)]
+pub struct HostInfo {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+}
+
+impl From<&Host> for HostInfo {
+    fn from(host: &Host) -> Self {
+        HostInfo {
+            id: host.id.clone(),
+            name: host.name.clone(),
+            url: host.url.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct HostList {
+    pub hosts: Vec<HostInfo>,
+    // None is this machine
+    pub active: Option<String>,
+}
+
+// a card of the overview, id None is this machine
+#[derive(Serialize)]
+pub struct HostOverview {
+    pub id: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub summary: Option<HostSummary>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Saved {
+    hosts: Vec<Host>,
+    active: Option<String>,
+}
+
+// errors of commands are mostly plain strings, which should not end up quoted
+pub fn error_message(err: Value) -> String {
+    match err {
+        Value::String(message) => message,
+        err => err.to_string(),
+    }
+}
+
+pub struct Hosts {
+    path: PathBuf,
+    hosts: Mutex<Vec<Host>>,
+    active: Arc<Mutex<Option<String>>>,
+    clients: Mutex<HashMap<String, Arc<RemoteClient>>>,
+    on_event: EventSink,
+}
+
+impl Hosts {
+    // `on_event` gets the events of the active host only
+    pub fn load(path: PathBuf, on_event: EventSink) -> Self {
+        let saved: Saved = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        let active = saved
+            .active
+            .filter(|id| saved.hosts.iter().any(|host| &host.id == id));
+        Hosts {
+            path,
+            hosts: Mutex::new(saved.hosts),
+            active: Arc::new(Mutex::new(active)),
+            clients: Mutex::new(HashMap::new()),
+            on_event,
+        }
+    }
+
+    pub fn list(&self) -> HostList {
+        HostList {
+            hosts: self
+                .hosts
+                .lock()
+                .unwrap()
+                .iter()
+                .map(HostInfo::from)
+                .collect(),
+            active: self.active.lock().unwrap().clone(),
+        }
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.active.lock().unwrap().is_none()
+    }
+
+    // only keeps hosts that could be reached with their token
+    pub fn add(&self, name: String, url: String, token: String) -> Result<HostInfo, String> {
+        let host = Host {
+            id: format!("{:x}", now_millis()),
+            name,
+            url,
+            token,
+        };
+        let client = self.connect(&host)?;
+        client
+            .request("os_info", Value::Null)
+            .map_err(|err| err.to_string())?
+            .map_err(error_message)?;
+        self.clients
+            .lock()
+            .unwrap()
+            .insert(host.id.clone(), Arc::new(client));
+        let info = HostInfo::from(&host);
+        self.hosts.lock().unwrap().push(host);
+        self.save();
+        Ok(info)
+    }
+
+    pub fn remove(&self, id: &str) {
+        self.hosts.lock().unwrap().retain(|host| host.id != id);
+        self.clients.lock().unwrap().remove(id);
+        let mut active = self.active.lock().unwrap();
+        if active.as_deref() == Some(id) {
+            *active = None;
+        }
+        drop(active);
+        self.save();
+    }
+
+    pub fn set_active(&self, id: Option<String>) -> Result<(), String> {
+        if let Some(id) = &id {
+            self.client(id)?;
+        }
+        *self.active.lock().unwrap() = id;
+        self.save();
+        Ok(())
+    }
+
+    // runs a command on the active host
+    pub fn request(&self, command: &str, args: Value) -> Result<Value, Value> {
+        let id = self
+            .active
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| Value::String("no remote host is active".into()))?;
+        self.request_host(&id, command, args)
+    }
+
+    // reconnects once if the connection was gone before the request went out, a request
+    // that timed out or lost its connection on the way may have run and is not sent again
+    pub fn request_host(&self, id: &str, command: &str, args: Value) -> Result<Value, Value> {
+        let client = self.client(id).map_err(Value::String)?;
+        let failed = |err: RequestError| Value::String(err.to_string());
+        match client.request(command, args.clone()) {
+            Ok(result) => result,
+            Err(RequestError::Unsent) => {
+                self.clients.lock().unwrap().remove(id);
+                let client = self.client(id).map_err(Value::String)?;
+                client.request(command, args).map_err(failed)?
+            }
+            Err(RequestError::TimedOut) => Err(failed(RequestError::TimedOut)),
+            Err(err) => {
+                self.clients.lock().unwrap().remove(id);
+                Err(failed(err))
+            }
+        }
+    }
+
+    // this machine first, then every remote host asked at the same time
+    pub fn overview(&self, local: HostSummary) -> Vec<HostOverview> {
+        let hosts = self.hosts.lock().unwrap().clone();
+        let remote: Vec<HostOverview> = thread::scope(|scope| {
+            let requests: Vec<_> = hosts
+                .iter()
+                .map(|host| {
+                    scope.spawn(move || {
+                        self.request_host(&host.id, "host_summary", Value::Null)
+                            .and_then(|summary| {
+                                serde_json::from_value(summary)
+                                    .map_err(|err| Value::String(err.to_string()))
+                            })
+                    })
+                })
+                .collect();
+            hosts
+                .iter()
+                .zip(requests)
+                .map(|(host, request)| {
+                    let (summary, error) = match request.join() {
+                        Ok(Ok(summary)) => (Some(summary), None),
+                        Ok(Err(err)) => (None, Some(error_message(err))),
+                        Err(_) => (None, Some("request failed".to_string())),
+                    };
+                    HostOverview {
+                        id: Some(host.id.clone()),
+                        name: host.name.clone(),
+                        url: host.url.clone(),
+                        summary,
+                        error,
+                    }
+                })
+                .collect()
+        });
+        let mut overview = vec![HostOverview {
+            id: None,
+            name: "This machine".into(),
+            url: String::new(),
+            summary: Some(local),
+            error: None,
+        }];
+        overview.extend(remote);
+        overview
+    }
+
+    fn client(&self, id: &str) -> Result<Arc<RemoteClient>, String> {
+        if let Some(client) = self.clients.lock().unwrap().get(id) {
+            return Ok(client.clone());
+        }
+        let host = self
+            .hosts
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|host| host.id == id)
+            .cloned()
+            .ok_or_else(|| format!("unknown host {}", id))?;
+        let client = Arc::new(self.connect(&host)?);
+        self.clients.lock().unwrap().insert(host.id, client.clone());
+        Ok(client)
+    }
+
+    fn connect(&self, host: &Host) -> Result<RemoteClient, String> {
+        let id = host.id.clone();
+        let active = self.active.clone();
+        let on_event = self.on_event.clone();
+        RemoteClient::connect(
+            &host.url,
+            &host.token,
+            Arc::new(move |event, payload| {
+                if active.lock().unwrap().as_deref() == Some(id.as_str()) {
+                    on_event(event, payload);
+                }
+            }),
+        )
+        .map_err(|err| format!("{}: {}", host.url, err))
+    }
+
+    fn save(&self) {
+        let saved = Saved {
+            hosts: self.hosts.lock().unwrap().clone(),
+            active: self.active.lock().unwrap().clone(),
+        };
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(content) = serde_json::to_vec_pretty(&saved) {
+            let _ = write_private(&self.path, &content);
+        }
+    }
+}
+
+// the tokens are in there, so only the user may read it
+fn write_private(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    // a file from before keeps its mode when opened
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tungstenite::Message;
+
+    // an agent that takes every request and never answers
+    fn silent_agent(received: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let received = received.clone();
+                thread::spawn(move || {
+                    let mut socket = tungstenite::accept(stream).unwrap();
+                    while let Ok(message) = socket.read() {
+                        if let Message::Text(_) = message {
+                            received.fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn timed_out_requests_are_not_sent_again() {
+        let received = Arc::new(AtomicUsize::new(0));
+        let host = Host {
+            id: "agent".into(),
+            name: "agent".into(),
+            url: silent_agent(received.clone()),
+            token: "token".into(),
+        };
+        let hosts = Hosts::load(PathBuf::new(), Arc::new(|_, _| {}));
+        let client = hosts
+            .connect(&host)
+            .unwrap()
+            .with_timeout(Duration::from_millis(200));
+        hosts
+            .clients
+            .lock()
+            .unwrap()
+            .insert(host.id.clone(), Arc::new(client));
+        hosts.hosts.lock().unwrap().push(host);
+
+        let result = hosts.request_host("agent", "kill_process", json!({ "pid": 4242 }));
+        assert_eq!(
+            result,
+            Err(Value::String("the agent did not answer".into()))
+        );
+        thread::sleep(Duration::from_millis(300));
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,363 @@
+// watches the memory of the recorded processes and flags the ones that only ever grow,
+// only what history keeps is seen, which always includes the biggest processes
+use crate::history::{Record, TimeRange};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// memory may drop this much below its peak and still count as growing,
+// the allocator hands some back now and then
+const TOLERANCE: f64 = 0.02;
+// samples a process needs within the window before it is judged
+const MIN_SAMPLES: usize = 6;
+// and the part of the window they have to cover
+const MIN_COVERAGE: f64 = 0.8;
+const HOUR_MS: f64 = 60.0 * 60.0 * 1000.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct LeakSettings {
+    pub window_secs: u64,
+    // bytes per hour
+    pub min_growth: u64,
+}
+
+impl Default for LeakSettings {
+    fn default() -> Self {
+        LeakSettings {
+            window_secs: 30 * 60,
+            min_growth: 50 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Leak {
+    pub pid: u32,
+    pub name: String,
+    pub start_time: u64,
+    // bytes
+    pub memory: u64,
+    // bytes per hour
+    pub growth: u64,
+    // the first sample of the window
+    pub since: u64,
+}
+
+#[derive(Serialize)]
+pub struct MemoryPoint {
+    pub timestamp: u64,
+    pub memory: u64,
+}
+
+struct Samples {
+    name: String,
+    // timestamp and bytes, oldest first
+    memory: VecDeque<(u64, u64)>,
+}
+
+// fed with every history record, keeps the samples of the last window per process
+pub struct Leaks {
+    path: PathBuf,
+    settings: Mutex<LeakSettings>,
+    processes: Mutex<HashMap<(u32, u64), Samples>>,
+}
+
+impl Leaks {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        Leaks {
+            path,
+            settings: Mutex::new(settings),
+            processes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn settings(&self) -> LeakSettings {
+        *self.settings.lock().unwrap()
+    }
+
+    pub fn set_settings(&self, settings: LeakSettings) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(&settings)?)?;
+        *self.settings.lock().unwrap() = settings;
+        Ok(())
+    }
+
+    // the range the history has to be read from after a start
+    pub fn window(&self, now: u64) -> TimeRange {
+        TimeRange {
+            start: now.saturating_sub(self.settings().window_secs * 1000),
+            end: now,
+        }
+    }
+
+    // starts over from the recorded history, after a start or a change of the window,
+    // keeping what was observed after the last of `records` while they were read
+    pub fn replay(&self, records: &[Record]) {
+        let settings = self.settings();
+        let mut processes = self.processes.lock().unwrap();
+        let observed = std::mem::take(&mut *processes);
+        for record in records {
+            observe(&mut processes, &settings, record);
+        }
+        let replayed = records.last().map_or(0, |record| record.timestamp);
+        for (key, samples) in observed {
+            let newer = samples.memory.into_iter().filter(|(at, _)| *at > replayed);
+            let entry = processes.entry(key).or_insert_with(|| Samples {
+                name: samples.name,
+                memory: VecDeque::new(),
+            });
+            entry.memory.extend(newer);
+        }
+        processes.retain(|_, samples| !samples.memory.is_empty());
+    }
+
+    pub fn observe(&self, record: &Record) {
+        let settings = self.settings();
+        observe(&mut self.processes.lock().unwrap(), &settings, record);
+    }
+
+    // the processes whose memory grew steadily and fast enough over the window
+    pub fn leaks(&self) -> Vec<Leak> {
+        let settings = self.settings();
+        let window = (settings.window_secs * 1000) as f64;
+        let processes = self.processes.lock().unwrap();
+        let mut leaks: Vec<Leak> = processes
+            .iter()
+            .filter_map(|((pid, start_time), samples)| {
+                let memory = &samples.memory;
+                let (first, last) = (memory.front()?, memory.back()?);
+                if memory.len() < MIN_SAMPLES
+                    || ((last.0 - first.0) as f64) < window * MIN_COVERAGE
+                    || last.1 <= first.1
+                {
+                    return None;
+                }
+                let mut peak = 0;
+                for (_, bytes) in memory {
+                    if (*bytes as f64) < peak as f64 * (1.0 - TOLERANCE) {
+                        return None;
+                    }
+                    peak = peak.max(*bytes);
+                }
+                let growth = slope(memory) * HOUR_MS;
+                (growth >= settings.min_growth as f64).then(|| Leak {
+                    pid: *pid,
+                    name: samples.name.clone(),
+                    start_time: *start_time,
+                    memory: last.1,
+                    growth: growth as u64,
+                    since: first.0,
+                })
+            })
+            .collect();
+        leaks.sort_by_key(|leak| std::cmp::Reverse(leak.growth));
+        leaks
+    }
+
+    // the memory samples of the newest process with this pid
+    pub fn memory(&self, pid: u32) -> Vec<MemoryPoint> {
+        let processes = self.processes.lock().unwrap();
+        processes
+            .iter()
+            .filter(|((id, _), _)| *id == pid)
+            .max_by_key(|((_, start_time), _)| *start_time)
+            .map(|(_, samples)| {
+                samples
+                    .memory
+                    .iter()
+                    .map(|(timestamp, memory)| MemoryPoint {
+                        timestamp: *timestamp,
+                        memory: *memory,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn observe(processes: &mut HashMap<(u32, u64), Samples>, settings: &LeakSettings, record: &Record) {
+    let oldest = record.timestamp.saturating_sub(settings.window_secs * 1000);
+    for process in &record.processes {
+        let samples = processes
+            .entry((process.pid, process.start_time))
+            .or_insert_with(|| Samples {
+                name: process.name.clone(),
+                memory: VecDeque::new(),
+            });
+        // a process in the top by cpu and by memory is in the record twice
+        if samples.memory.back().map(|(at, _)| *at) != Some(record.timestamp) {
+            samples.memory.push_back((record.timestamp, process.memory));
+        }
+    }
+    processes.retain(|_, samples| {
+        while samples.memory.front().is_some_and(|(at, _)| *at < oldest) {
+            samples.memory.pop_front();
+        }
+        !samples.memory.is_empty()
+    });
+}
+
+// least squares, in bytes per millisecond
+fn slope(samples: &VecDeque<(u64, u64)>) -> f64 {
+    let n = samples.len() as f64;
+    let t0 = samples.front().map_or(0, |(at, _)| *at);
+    let (mut sum_x, mut sum_y, mut sum_xy, mut sum_xx) = (0.0, 0.0, 0.0, 0.0);
+    for (at, bytes) in samples {
+        let x = (at - t0) as f64;
+        let y = *bytes as f64;
+        sum_x += x;
+        sum_y += y;
+        sum_xy += x * y;
+        sum_xx += x * x;
+    }
+    let denominator = n * sum_xx - sum_x * sum_x;
+    if denominator == 0.0 {
+        0.0
+    } else {
+        (n * sum_xy - sum_x * sum_y) / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::ProcessSample;
+
+    const MB: u64 = 1024 * 1024;
+    const MINUTE_MS: u64 = 60 * 1000;
+
+    fn record(timestamp: u64, memory: u64) -> Record {
+        Record {
+            timestamp,
+            cpu: 0.0,
+            memory_used: 0,
+            memory_total: 0,
+            swap_used: 0,
+            received: 0,
+            transmitted: 0,
+            processes: vec![ProcessSample {
+                pid: 1,
+                name: "leaky".into(),
+                cpu: 0.0,
+                memory,
+                start_time: 1,
+            }],
+            started: Vec::new(),
+            ended: Vec::new(),
+            alerts: Vec::new(),
+        }
+    }
+
+    // ten minutes window, a sample every minute
+    fn watch(memory: &[u64]) -> Leaks {
+        let leaks = Leaks {
+            path: PathBuf::new(),
+            settings: Mutex::new(LeakSettings {
+                window_secs: 10 * 60,
+                min_growth: 10 * MB,
+            }),
+            processes: Mutex::new(HashMap::new()),
+        };
+        for (minute, bytes) in memory.iter().enumerate() {
+            leaks.observe(&record(minute as u64 * MINUTE_MS, *bytes));
+        }
+        leaks
+    }
+
+    fn growing(per_minute: u64) -> Vec<u64> {
+        (0..=10)
+            .map(|minute| 100 * MB + minute * per_minute)
+            .collect()
+    }
+
+    #[test]
+    fn slope_of_a_line() {
+        let samples = VecDeque::from([(1000, 100), (2000, 200), (3000, 300)]);
+        assert!((slope(&samples) - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slope_of_flat_or_single_samples() {
+        assert_eq!(slope(&VecDeque::from([(1000, 100), (2000, 100)])), 0.0);
+        assert_eq!(slope(&VecDeque::from([(1000, 100)])), 0.0);
+    }
+
+    #[test]
+    fn steady_growth_is_a_leak() {
+        let leaks = watch(&growing(MB)).leaks();
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].pid, 1);
+        assert_eq!(leaks[0].memory, 110 * MB);
+        assert_eq!(leaks[0].growth, 60 * MB);
+        assert_eq!(leaks[0].since, 0);
+    }
+
+    #[test]
+    fn slow_growth_is_not() {
+        assert!(watch(&growing(MB / 10)).leaks().is_empty());
+    }
+
+    #[test]
+    fn small_drops_are_tolerated() {
+        let mut memory = growing(MB);
+        // 1% below the peak before it
+        memory[5] = memory[4] - memory[4] / 100;
+        assert_eq!(watch(&memory).leaks().len(), 1);
+    }
+
+    #[test]
+    fn larger_drops_are_not() {
+        let mut memory = growing(MB);
+        memory[5] = memory[4] - memory[4] / 20;
+        assert!(watch(&memory).leaks().is_empty());
+    }
+
+    #[test]
+    fn needs_most_of_the_window() {
+        assert!(watch(&growing(MB)[..7]).leaks().is_empty());
+    }
+
+    #[test]
+    fn drops_samples_older_than_the_window() {
+        let leaks = watch(
+            &(0..=20)
+                .map(|minute| 100 * MB + minute * MB)
+                .collect::<Vec<_>>(),
+        );
+        let memory = leaks.memory(1);
+        assert_eq!(memory.len(), 11);
+        assert_eq!(memory[0].timestamp, 10 * MINUTE_MS);
+    }
+
+    #[test]
+    fn replay_keeps_what_was_observed_meanwhile() {
+        let leaks = watch(&[]);
+        leaks.observe(&record(11 * MINUTE_MS, 111 * MB));
+        let records: Vec<Record> = growing(MB)
+            .iter()
+            .enumerate()
+            .map(|(minute, bytes)| record(minute as u64 * MINUTE_MS, *bytes))
+            .collect();
+        leaks.replay(&records);
+        let timestamps: Vec<u64> = leaks
+            .memory(1)
+            .iter()
+            .map(|point| point.timestamp)
+            .collect();
+        assert_eq!(
+            timestamps,
+            (0..=11)
+                .map(|minute| minute * MINUTE_MS)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,176 +1,595 @@
-use serde::{Deserialize, Serialize};
-use std::env;
-use sysinfo::{Disk, Disks, Networks, Pid, System};
+mod actions;
+pub mod agent;
+mod alerts;
+mod audit;
+pub mod cli;
+pub mod collectors;
+pub mod columns;
+mod disk_io;
+mod disk_usage;
+mod export;
+mod exporter;
+mod history;
+mod hosts;
+mod leaks;
+mod mounts;
+mod network;
+mod ports;
+pub mod process_history;
+mod remote;
+mod removable;
+pub mod sampler;
+mod settings;
+mod threads;
+mod tui;
 
-#[derive(Serialize, Deserialize)]
-struct SystemInfo {
-    name: String,
-}
+use actions::Actions;
+use alerts::{AlertEvent, AlertKind, Alerts, FiringAlert, Rule};
+use audit::{Audit, AuditEntry};
+use collectors::{DiskInfo, ProccessInfo, SysMetrics, SystemInfo};
+use columns::Column;
+use disk_io::{DiskIoInfo, DiskIoSampler};
+use disk_usage::{DiskScanner, ScanNode};
+use export::{ExportFormat, ExportSource};
+use exporter::{Exporter, ExporterConfig, Render};
+use history::{History, MetricPoint, ProcessDiff, Record, Recorder, TimeRange};
+use hosts::{HostInfo, HostList, HostOverview, Hosts};
+use leaks::{Leak, LeakSettings, Leaks, MemoryPoint};
+use mounts::DiskSampler;
+use network::{InterfaceInfo, NetworkSampler};
+use ports::PortInfo;
+use process_history::ProcessPoint;
+use removable::UnmountError;
+use sampler::SystemSampler;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use settings::{Settings, SettingsStore, WindowSize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, LogicalSize, Manager, State, Window, WindowEvent};
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_notification::NotificationExt;
+use threads::{ThreadInfo, ThreadSampler};
 
 #[tauri::command]
 fn os_info() -> SystemInfo {
-    SystemInfo {
-        name: env::consts::OS.to_string(),
-    }
+    collectors::system_info()
 }
 
-#[derive(Serialize, Deserialize, PartialEq, PartialOrd)]
-struct ProccessInfo {
-    id: String,
-    name: String,
-    cpu: f32,
-    memory: u64,
-    disk_read: u64,
-    disk_write: u64,
-}
-
-#[tauri::command]
-fn process_info(name: Option<String>) -> Vec<ProccessInfo> {
-    let mut sys = System::new();
-    sys.refresh_all();
-
-    let mut processes: Vec<ProccessInfo> = sys
-        .processes()
-        .iter()
-        .map(|(id, process)| ProccessInfo {
-            id: id.to_string(),
-            name: process.name().to_string_lossy().into_owned(),
-            cpu: process.cpu_usage(),
-            memory: process.memory() / 1024 / 1024,
-            disk_read: process.disk_usage().read_bytes / 1024 / 1024,
-            disk_write: process.disk_usage().written_bytes / 1024 / 1024,
-        })
-        .filter(|proc| {
-            if let Some(name) = &name {
-                proc.name.starts_with(&*name)
-            } else {
-                true
-            }
-        })
-        .collect();
-    processes.sort_by_key(|e| e.name.clone());
-    processes
+#[tauri::command]
+fn process_info(
+    name: Option<String>,
+    columns: Option<Vec<Column>>,
+    sampler: State<'_, SystemSampler>,
+) -> Vec<ProccessInfo> {
+    let columns = columns.unwrap_or_default();
+    // the app's own window is the only client of its sampler
+    sampler.with_trends(&columns, 0, |sys, trends, window| {
+        collectors::process_infos_with_trends(sys, trends, window, name, &columns)
+    })
+}
+
+#[tauri::command]
+fn get_settings(store: State<'_, SettingsStore>) -> Settings {
+    store.get()
+}
+
+// only the fields sent are changed, returns the settings as they were kept
+#[tauri::command]
+fn update_settings(
+    patch: Map<String, Value>,
+    store: State<'_, SettingsStore>,
+) -> Result<Settings, String> {
+    store.patch(patch).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn process_threads(pid: u32, sampler: State<'_, ThreadSampler>) -> Vec<ThreadInfo> {
+    sampler.sample(pid)
+}
+
+#[tauri::command]
+fn process_history(
+    pid: u32,
+    start_time: u64,
+    sampler: State<'_, SystemSampler>,
+) -> Vec<ProcessPoint> {
+    sampler.process_history(pid, start_time)
 }
 
 #[tauri::command]
 fn kill_process(id: usize) -> Option<bool> {
-    let mut sys = System::new();
-    sys.refresh_all();
-    let result = sys
-        .processes()
-        .get(&Pid::from(id))
-        .map(|process| process.kill());
-    result
-}
-
-fn format_memory_size(bytes: u64) -> String {
-    const KB: f64 = 1024.0;
-    const MB: f64 = KB * 1024.0;
-    const GB: f64 = MB * 1024.0;
-    const TB: f64 = GB * 1024.0;
-
-    let size = bytes as f64;
-    let (value, unit) = if size >= TB {
-        (size / TB, "TB")
-    } else if size >= GB {
-        (size / GB, "GB")
-    } else if size >= MB {
-        (size / MB, "MB")
-    } else if size >= KB {
-        (size / KB, "KB")
-    } else {
-        (size, "bytes")
+    collectors::kill_process(id)
+}
+
+#[tauri::command]
+fn signal_process(id: usize, signal: String) -> Option<bool> {
+    collectors::signal_process(id, &signal)
+}
+
+#[tauri::command]
+fn file_systems(hide_pseudo: Option<bool>, sampler: State<'_, DiskSampler>) -> Vec<DiskInfo> {
+    sampler.with_disks(|disks| collectors::disk_infos(disks, hide_pseudo.unwrap_or(false)))
+}
+
+#[tauri::command]
+fn unmount(mount_point: String, sampler: State<'_, DiskSampler>) -> Result<(), UnmountError> {
+    removable::unmount_removable(&sampler, Path::new(&mount_point))
+}
+
+#[tauri::command]
+fn eject(device: String, sampler: State<'_, DiskSampler>) -> Result<(), UnmountError> {
+    removable::eject_removable(&sampler, &device)
+}
+
+#[tauri::command]
+fn analyze_disk(app: AppHandle, scanner: State<'_, DiskScanner>, path: String) {
+    scanner.start(PathBuf::from(path), move |progress| {
+        let _ = app.emit("disk-scan-progress", progress);
+    });
+}
+
+#[tauri::command]
+fn cancel_disk_scan(scanner: State<'_, DiskScanner>) {
+    scanner.cancel();
+}
+
+#[tauri::command]
+fn disk_scan_node(scanner: State<'_, DiskScanner>, path: String) -> Option<ScanNode> {
+    scanner.node(Path::new(&path))
+}
+
+#[tauri::command]
+fn disk_io(sampler: State<'_, DiskIoSampler>) -> Vec<DiskIoInfo> {
+    sampler.sample()
+}
+
+#[tauri::command]
+fn get_metrics(
+    sampler: State<'_, SystemSampler>,
+    networks: State<'_, NetworkSampler>,
+) -> SysMetrics {
+    sampler
+        .with_system(|sys| networks.with_networks(|networks, _| collectors::metrics(sys, networks)))
+}
+
+#[tauri::command]
+fn network_interfaces(sampler: State<'_, NetworkSampler>) -> Vec<InterfaceInfo> {
+    sampler.sample()
+}
+
+#[tauri::command]
+fn listening_ports() -> Vec<PortInfo> {
+    ports::listening_ports()
+}
+
+#[tauri::command]
+fn history(
+    range: TimeRange,
+    resolution: u64,
+    history: State<'_, History>,
+) -> Result<Vec<MetricPoint>, String> {
+    history
+        .query(range, resolution)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn history_bounds(history: State<'_, History>) -> Result<Option<TimeRange>, String> {
+    history.bounds().map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn history_snapshot(timestamp: u64, history: State<'_, History>) -> Result<Option<Record>, String> {
+    history.snapshot(timestamp).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn history_diff(range: TimeRange, history: State<'_, History>) -> Result<ProcessDiff, String> {
+    history.diff(range).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn alert_rules(alerts: State<'_, Alerts>) -> Vec<Rule> {
+    alerts.rules()
+}
+
+#[tauri::command]
+fn set_alert_rules(rules: Vec<Rule>, alerts: State<'_, Alerts>) -> Result<(), String> {
+    alerts.set_rules(rules).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn firing_alerts(alerts: State<'_, Alerts>) -> Vec<FiringAlert> {
+    alerts.firing()
+}
+
+#[tauri::command]
+fn alert_history(range: TimeRange, history: State<'_, History>) -> Result<Vec<AlertEvent>, String> {
+    history.alerts(range).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn audit_log(limit: usize, audit: State<'_, Audit>) -> Result<Vec<AuditEntry>, String> {
+    audit.entries(limit).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn leak_settings(leaks: State<'_, Leaks>) -> LeakSettings {
+    leaks.settings()
+}
+
+// a longer window is filled from history right away
+#[tauri::command]
+async fn set_leak_settings(app: AppHandle, settings: LeakSettings) -> Result<(), String> {
+    app.state::<Leaks>()
+        .set_settings(settings)
+        .map_err(|err| err.to_string())?;
+    blocking(move || replay_leaks(&app)).await
+}
+
+// reading a week of history takes a while, the leaks just start out empty if it fails
+fn replay_leaks(app: &AppHandle) {
+    let leaks = app.state::<Leaks>();
+    match app
+        .state::<History>()
+        .records(leaks.window(history::now_millis()))
+    {
+        Ok(records) => leaks.replay(&records),
+        Err(err) => eprintln!("could not read the history for memory leaks: {}", err),
+    }
+}
+
+#[tauri::command]
+fn memory_leaks(leaks: State<'_, Leaks>) -> Vec<Leak> {
+    leaks.leaks()
+}
+
+#[tauri::command]
+fn process_memory(pid: u32, leaks: State<'_, Leaks>) -> Vec<MemoryPoint> {
+    leaks.memory(pid)
+}
+
+// asks where to save and writes the export there, returns the chosen path
+#[tauri::command]
+async fn export(
+    app: AppHandle,
+    source: ExportSource,
+    format: ExportFormat,
+) -> Result<Option<String>, String> {
+    let extension = format.extension();
+    let dialog = app
+        .dialog()
+        .file()
+        .add_filter(extension, &[extension])
+        .set_file_name(format!("{}.{}", source.file_stem(), extension));
+    // the dialog stays open as long as the user likes, off the async workers
+    let Some(path) = blocking(move || dialog.blocking_save_file()).await? else {
+        return Ok(None);
     };
+    let path = path.into_path().map_err(|err| err.to_string())?;
+    let written = path.to_string_lossy().into_owned();
+    blocking(move || write_export(&app, source, format, &path)).await??;
+    Ok(Some(written))
+}
+
+// remote rows come from the active host, written to a file on this machine
+fn write_export(
+    app: &AppHandle,
+    source: ExportSource,
+    format: ExportFormat,
+    path: &Path,
+) -> Result<(), String> {
+    let hosts = app.state::<Hosts>();
+    match source {
+        ExportSource::Processes { name } if !hosts.is_local() => {
+            let rows: Vec<export::ProcessRow> =
+                remote_rows(&hosts, "process_rows", serde_json::json!({ "name": name }))?;
+            export::write(&rows, format, path)
+        }
+        ExportSource::FileSystems { hide_pseudo } if !hosts.is_local() => {
+            let rows: Vec<export::FileSystemRow> = remote_rows(
+                &hosts,
+                "file_system_rows",
+                serde_json::json!({ "hidePseudo": hide_pseudo }),
+            )?;
+            export::write(&rows, format, path)
+        }
+        ExportSource::History { range, resolution } if !hosts.is_local() => {
+            let points: Vec<MetricPoint> = remote_rows(
+                &hosts,
+                "history",
+                serde_json::json!({ "range": range, "resolution": resolution }),
+            )?;
+            export::write(&points, format, path)
+        }
+        ExportSource::Processes { name } => {
+            let rows = app
+                .state::<SystemSampler>()
+                .with_system(|sys| export::process_rows(sys, name.as_deref()));
+            export::write(&rows, format, path)
+        }
+        ExportSource::FileSystems { hide_pseudo } => {
+            let rows = app
+                .state::<DiskSampler>()
+                .with_disks(|disks| export::file_system_rows(disks, hide_pseudo));
+            export::write(&rows, format, path)
+        }
+        ExportSource::History { range, resolution } => app
+            .state::<History>()
+            .query(range, resolution)
+            .and_then(|points| export::write(&points, format, path)),
+    }
+    .map_err(|err| err.to_string())
+}
+
+fn remote_rows<T: DeserializeOwned>(
+    hosts: &Hosts,
+    command: &str,
+    args: Value,
+) -> Result<Vec<T>, String> {
+    hosts
+        .request(command, args)
+        .and_then(|rows| serde_json::from_value(rows).map_err(|err| Value::String(err.to_string())))
+        .map_err(hosts::error_message)
+}
+
+#[tauri::command]
+fn exporter_config(exporter: State<'_, Exporter>) -> ExporterConfig {
+    exporter.config()
+}
+
+#[tauri::command]
+fn set_exporter_config(
+    app: AppHandle,
+    config: ExporterConfig,
+    exporter: State<'_, Exporter>,
+    store: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    exporter
+        .apply(config, exporter_render(app))
+        .map_err(|err| err.to_string())?;
+    store
+        .update(|settings| settings.exporter = config)
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn hosts(hosts: State<'_, Hosts>) -> HostList {
+    hosts.list()
+}
+
+// talking to an agent blocks, so it runs where the async workers do not wait for it
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn add_host(
+    app: AppHandle,
+    name: String,
+    url: String,
+    token: String,
+) -> Result<HostInfo, String> {
+    blocking(move || app.state::<Hosts>().add(name, url, token)).await?
+}
 
-    format!("{:.2} {}", value, unit)
+#[tauri::command]
+fn remove_host(id: String, hosts: State<'_, Hosts>) {
+    hosts.remove(&id);
 }
 
-#[derive(Serialize)]
-struct DiskInfo {
-    device: String,
-    directory: String,
-    r#type: String,
-    total: String,
-    available: String,
-    used: String,
+#[tauri::command]
+async fn set_active_host(app: AppHandle, id: Option<String>) -> Result<(), String> {
+    blocking(move || app.state::<Hosts>().set_active(id)).await?
 }
 
+// a summary of every known host, asking the remote ones can take a moment
 #[tauri::command]
-fn file_systems() -> Vec<DiskInfo> {
-    let disks = Disks::new_with_refreshed_list();
-    let mut file_systems = Vec::new();
-    for disk in &disks {
-        let file_system = DiskInfo {
-            device: disk.name().to_string_lossy().to_string(),
-            directory: disk.mount_point().to_string_lossy().to_string(),
-            r#type: disk.file_system().to_string_lossy().to_string(),
-            total: format_memory_size(disk.total_space()),
-            available: format_memory_size(disk.available_space()),
-            used: format_memory_size(disk.total_space() - disk.available_space()),
+async fn host_overview(app: AppHandle) -> Vec<HostOverview> {
+    blocking(move || {
+        let local = app.state::<SystemSampler>().with_system(|sys| {
+            app.state::<DiskSampler>()
+                .with_disks(|disks| collectors::summary(sys, disks))
+        });
+        app.state::<Hosts>().overview(local)
+    })
+    .await
+    .unwrap_or_default()
+}
+
+// the frontend sends the commands an agent serves here while a remote host is active
+#[tauri::command]
+async fn remote_invoke(app: AppHandle, command: String, args: Value) -> Result<Value, Value> {
+    blocking(move || app.state::<Hosts>().request(&command, args))
+        .await
+        .map_err(Value::String)?
+}
+
+// the exporter reads the same samplers as the pages
+fn exporter_render(app: AppHandle) -> Render {
+    Arc::new(move || {
+        app.state::<SystemSampler>().with_system(|sys| {
+            app.state::<NetworkSampler>().with_networks(|networks, _| {
+                app.state::<DiskSampler>().with_disks(|disks| {
+                    exporter::render(sys, networks, disks, &disk_io::counters())
+                })
+            })
+        })
+    })
+}
+
+const MOUNT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+fn watch_mounts(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(MOUNT_POLL_INTERVAL);
+        if let Some(change) = app.state::<DiskSampler>().poll_mounts() {
+            // a remote host forwards its own
+            if app.state::<Hosts>().is_local() {
+                let _ = app.emit("mounts-changed", change);
+            }
+        }
+    });
+}
+
+fn notify(app: &AppHandle, events: &[AlertEvent]) {
+    for event in events {
+        let title = match event.kind {
+            AlertKind::Fired => format!("Alert: {}", event.rule_name),
+            AlertKind::Resolved => format!("Resolved: {}", event.rule_name),
         };
-        file_systems.push(file_system);
-    }
-    file_systems
-}
-
-#[derive(Serialize)]
-struct SysMetrics {
-    cpus: Vec<f32>,
-    used_memory: String,
-    total_memory: String,
-    free_memory: String,
-    used_swap: String,
-    total_swap: String,
-    free_swap: String,
-    //network
-    recived: u64,
-    total_recived: u64,
-    transmited: u64,
-    total_transmited: u64,
-    // disk
-}
-
-#[tauri::command]
-fn get_metrics() -> SysMetrics {
-    let mut sys = System::new();
-    sys.refresh_all();
-
-    let network = Networks::new_with_refreshed_list();
-
-    SysMetrics {
-        cpus: sys.cpus().iter().map(|c| c.cpu_usage()).collect(),
-        used_memory: format_memory_size(sys.used_memory()),
-        total_memory: format_memory_size(sys.total_memory()),
-        free_memory: format_memory_size(sys.free_memory()),
-        used_swap: format_memory_size(sys.used_swap()),
-        total_swap: format_memory_size(sys.used_swap()),
-        free_swap: format_memory_size(sys.free_swap()),
-        recived: network.iter().map(|(name, iface)| iface.received()).sum(),
-        total_recived: network
-            .iter()
-            .map(|(name, iface)| iface.total_received())
-            .sum(),
-        transmited: network
-            .iter()
-            .map(|(name, iface)| iface.transmitted())
-            .sum(),
-        total_transmited: network
-            .iter()
-            .map(|(name, iface)| iface.total_transmitted())
-            .sum(),
+        let _ = app
+            .notification()
+            .builder()
+            .title(title)
+            .body(&event.message)
+            .show();
     }
 }
 
+fn record_history(app: AppHandle) {
+    thread::spawn(move || {
+        let mut recorder = Recorder::default();
+        let mut actions = Actions::default();
+        loop {
+            thread::sleep(history::RECORD_INTERVAL);
+            // alert rules are checked against the same sample that gets recorded
+            let record = app.state::<SystemSampler>().with_system(|sys| {
+                let mut record = recorder.sample(sys);
+                record.alerts = app.state::<DiskSampler>().with_disks(|disks| {
+                    app.state::<Alerts>().evaluate(sys, disks, record.timestamp)
+                });
+                record
+            });
+            app.state::<Leaks>().observe(&record);
+            notify(&app, &record.alerts);
+            actions.handle(
+                &app.state::<Audit>(),
+                &app.state::<Alerts>().rules(),
+                &record.alerts,
+            );
+            let _ = app.state::<History>().append(&record);
+        }
+    });
+}
+
+// remembered when the window is closed and restored at the next start
+fn save_window_size(window: &Window) {
+    let (Ok(size), Ok(scale)) = (window.inner_size(), window.scale_factor()) else {
+        return;
+    };
+    let size = size.to_logical::<f64>(scale);
+    let _ = window.state::<SettingsStore>().update(|settings| {
+        settings.window = Some(WindowSize {
+            width: size.width,
+            height: size.height,
+        })
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(SystemSampler::default())
+        .manage(ThreadSampler::default())
+        .manage(NetworkSampler::default())
+        .manage(DiskIoSampler::default())
+        .manage(DiskScanner::default())
+        .manage(DiskSampler::default())
+        .setup(|app| {
+            let history_dir = app.path().app_data_dir()?.join("history");
+            let history = History::open(history_dir)?;
+            let leaks = Leaks::load(app.path().app_config_dir()?.join("leaks.json"));
+            app.manage(history);
+            app.manage(leaks);
+            let handle = app.handle().clone();
+            thread::spawn(move || replay_leaks(&handle));
+            app.manage(Alerts::load(
+                app.path().app_config_dir()?.join("alerts.json"),
+            ));
+            app.manage(Audit::new(app.path().app_data_dir()?.join("audit.jsonl")));
+            let settings = SettingsStore::load(&app.path().app_config_dir()?);
+            if let (Some(window), Some(size)) =
+                (app.get_webview_window("main"), settings.get().window)
+            {
+                let _ = window.set_size(LogicalSize::new(size.width, size.height));
+            }
+            let exporter = Exporter::default();
+            // a taken port only leaves the exporter off
+            let _ = exporter.apply(
+                settings.get().exporter,
+                exporter_render(app.handle().clone()),
+            );
+            app.manage(exporter);
+            app.manage(settings);
+            let handle = app.handle().clone();
+            app.manage(Hosts::load(
+                app.path().app_config_dir()?.join("hosts.json"),
+                Arc::new(move |event, payload| {
+                    let _ = handle.emit(event, payload);
+                }),
+            ));
+            watch_mounts(app.handle().clone());
+            record_history(app.handle().clone());
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { .. } = event {
+                save_window_size(window);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             os_info,
             process_info,
+            process_threads,
+            process_history,
             kill_process,
+            signal_process,
             file_systems,
-            get_metrics
+            unmount,
+            eject,
+            analyze_disk,
+            cancel_disk_scan,
+            disk_scan_node,
+            disk_io,
+            get_metrics,
+            network_interfaces,
+            listening_ports,
+            history,
+            history_bounds,
+            history_snapshot,
+            history_diff,
+            alert_rules,
+            set_alert_rules,
+            firing_alerts,
+            alert_history,
+            audit_log,
+            leak_settings,
+            set_leak_settings,
+            memory_leaks,
+            process_memory,
+            export,
+            exporter_config,
+            set_exporter_config,
+            hosts,
+            add_host,
+            remove_host,
+            set_active_host,
+            host_overview,
+            remote_invoke,
+            get_settings,
+            update_settings
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
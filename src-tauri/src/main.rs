@@ -1,6 +1,12 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-fn main() {
-    proccessmanager_lib::run()
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    if let Some(code) = proccessmanager_lib::cli::run() {
+        return code;
+    }
+    proccessmanager_lib::run();
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,269 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use sysinfo::{DiskRefreshKind, Disks};
+
+#[derive(Serialize, Clone, PartialEq)]
+pub struct Mount {
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct MountChange {
+    pub added: Vec<Mount>,
+    pub removed: Vec<Mount>,
+}
+
+// long-lived disk list, only re-listed when the mount table changes
+pub struct DiskSampler {
+    inner: Mutex<(Disks, Vec<Mount>)>,
+}
+
+impl Default for DiskSampler {
+    fn default() -> Self {
+        DiskSampler {
+            inner: Mutex::new((Disks::new_with_refreshed_list(), mounts())),
+        }
+    }
+}
+
+impl DiskSampler {
+    // refreshes the space of the known disks and hands them to `f`
+    pub fn with_disks<R>(&self, f: impl FnOnce(&Disks) -> R) -> R {
+        let mut inner = self.inner.lock().unwrap();
+        for disk in inner.0.list_mut() {
+            disk.refresh_specifics(DiskRefreshKind::nothing().with_storage());
+        }
+        f(&inner.0)
+    }
+
+    // re-lists the disks if something was mounted or unmounted since the last poll,
+    // pseudo file systems are left out of the reported change
+    pub fn poll_mounts(&self) -> Option<MountChange> {
+        let current = mounts();
+        let mut inner = self.inner.lock().unwrap();
+        let (disks, known) = &mut *inner;
+        if *known == current {
+            return None;
+        }
+        disks.refresh(true);
+
+        let real = |mount: &&Mount| {
+            !is_pseudo(
+                &mount.file_system,
+                &mount.device,
+                Path::new(&mount.mount_point),
+            )
+        };
+        let change = MountChange {
+            added: current
+                .iter()
+                .filter(|m| !known.contains(m))
+                .filter(real)
+                .cloned()
+                .collect(),
+            removed: known
+                .iter()
+                .filter(|m| !current.contains(m))
+                .filter(real)
+                .cloned()
+                .collect(),
+        };
+        *known = current;
+        (!change.added.is_empty() || !change.removed.is_empty()).then_some(change)
+    }
+}
+
+pub fn mounts() -> Vec<Mount> {
+    fs::read_to_string("/proc/self/mountinfo")
+        .map(|content| parse_mounts(&content))
+        .unwrap_or_default()
+}
+
+fn parse_mounts(content: &str) -> Vec<Mount> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (mount, filesystem) = line.split_once(" - ")?;
+            let mut filesystem = filesystem.split(' ');
+            Some(Mount {
+                mount_point: unescape(mount.split(' ').nth(4)?),
+                file_system: filesystem.next()?.to_string(),
+                device: unescape(filesystem.next()?),
+            })
+        })
+        .collect()
+}
+
+pub struct InodeUsage {
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+}
+
+#[cfg(unix)]
+pub fn inode_usage(path: &Path) -> Option<InodeUsage> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    let total = stat.f_files as u64;
+    let free = stat.f_ffree as u64;
+    Some(InodeUsage {
+        total,
+        used: total.saturating_sub(free),
+        free,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn inode_usage(_path: &Path) -> Option<InodeUsage> {
+    None
+}
+
+// in-memory, image and kernel file systems that never run out of real disk
+const PSEUDO_FILE_SYSTEMS: &[&str] = &[
+    "tmpfs", "devtmpfs", "ramfs", "overlay", "squashfs", "proc", "sysfs", "cgroup", "cgroup2",
+    "devpts", "efivarfs", "autofs", "nsfs",
+];
+
+pub fn is_pseudo(file_system: &str, device: &str, mount_point: &Path) -> bool {
+    PSEUDO_FILE_SYSTEMS.contains(&file_system)
+        || device.starts_with("/dev/loop")
+        || mount_point.starts_with("/snap")
+}
+
+// mount point -> per-mount options followed by the super block options
+pub fn mount_options() -> HashMap<PathBuf, String> {
+    fs::read_to_string("/proc/self/mountinfo")
+        .map(|content| parse_options(&content))
+        .unwrap_or_default()
+}
+
+fn parse_options(content: &str) -> HashMap<PathBuf, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (mount, filesystem) = line.split_once(" - ")?;
+            let mount: Vec<&str> = mount.split(' ').collect();
+            let mount_point = unescape(mount.get(4)?);
+            let mut options: Vec<&str> = mount.get(5)?.split(',').collect();
+            for option in filesystem.split(' ').nth(2).unwrap_or("").split(',') {
+                if !option.is_empty() && !options.contains(&option) {
+                    options.push(option);
+                }
+            }
+            Some((PathBuf::from(mount_point), options.join(",")))
+        })
+        .collect()
+}
+
+// mountinfo escapes space, tab, newline and backslash as \ooo
+pub fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if let Some(code) = field
+                .get(i + 1..i + 4)
+                .and_then(|octal| u8::from_str_radix(octal, 8).ok())
+            {
+                out.push(code);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw,errors=remount-ro
+35 22 0:31 / /proc rw,nosuid,nodev,noexec,relatime shared:13 - proc proc rw
+61 22 8:17 / /media/user/My\\040Disk rw,nosuid,nodev,relatime shared:210 - vfat /dev/sdb1 rw,uid=1000,fmask=0022
+70 22 7:3 / /snap/core/123 ro,nodev,relatime shared:40 - squashfs /dev/loop3 ro
+";
+
+    #[test]
+    fn unescapes_octal_codes() {
+        for (field, unescaped) in [
+            ("/plain", "/plain"),
+            ("/My\\040Disk", "/My Disk"),
+            ("/tab\\011and\\012newline", "/tab\tand\nnewline"),
+            ("/back\\134slash", "/back\\slash"),
+            // not three octal digits, kept as it is
+            ("/short\\04", "/short\\04"),
+            ("/not\\089octal", "/not\\089octal"),
+            ("/end\\", "/end\\"),
+        ] {
+            assert_eq!(unescape(field), unescaped, "{}", field);
+        }
+    }
+
+    #[test]
+    fn parses_the_mount_table() {
+        let mounts = parse_mounts(MOUNTINFO);
+        assert_eq!(mounts.len(), 4);
+        assert!(
+            mounts[2]
+                == Mount {
+                    device: "/dev/sdb1".into(),
+                    mount_point: "/media/user/My Disk".into(),
+                    file_system: "vfat".into(),
+                }
+        );
+    }
+
+    #[test]
+    fn joins_mount_and_super_block_options() {
+        let options = parse_options(MOUNTINFO);
+        assert_eq!(options[Path::new("/")], "rw,relatime,errors=remount-ro");
+        // rw is in both lists, it is kept once
+        assert_eq!(
+            options[Path::new("/media/user/My Disk")],
+            "rw,nosuid,nodev,relatime,uid=1000,fmask=0022"
+        );
+        assert_eq!(
+            options[Path::new("/proc")],
+            "rw,nosuid,nodev,noexec,relatime"
+        );
+    }
+
+    #[test]
+    fn tells_pseudo_file_systems_apart() {
+        for (file_system, device, mount_point, pseudo) in [
+            ("ext4", "/dev/nvme0n1p2", "/", false),
+            ("vfat", "/dev/sdb1", "/media/user/My Disk", false),
+            ("tmpfs", "tmpfs", "/run", true),
+            ("proc", "proc", "/proc", true),
+            ("cgroup2", "cgroup2", "/sys/fs/cgroup", true),
+            ("ext4", "/dev/loop3", "/mnt/image", true),
+            ("squashfs", "/dev/sda1", "/mnt", true),
+            ("ext4", "/dev/sda1", "/snap/core/123", true),
+            ("ext4", "/dev/sda1", "/snapshots", false),
+        ] {
+            assert_eq!(
+                is_pseudo(file_system, device, Path::new(mount_point)),
+                pseudo,
+                "{} {} {}",
+                file_system,
+                device,
+                mount_point
+            );
+        }
+    }
+}
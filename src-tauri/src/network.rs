@@ -0,0 +1,88 @@
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+use sysinfo::Networks;
+
+#[derive(Serialize)]
+pub struct InterfaceInfo {
+    pub name: String,
+    pub mac: String,
+    pub addresses: Vec<String>,
+    pub mtu: u64,
+    pub is_virtual: bool,
+    // bytes per second since the previous sample
+    pub received: u64,
+    pub transmitted: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub total_packets_received: u64,
+    pub total_packets_transmitted: u64,
+    pub total_errors_on_received: u64,
+    pub total_errors_on_transmitted: u64,
+}
+
+// keeps one Networks instance alive so received/transmitted are real deltas
+pub struct NetworkSampler {
+    inner: Mutex<(Networks, Instant)>,
+}
+
+impl Default for NetworkSampler {
+    fn default() -> Self {
+        NetworkSampler {
+            inner: Mutex::new((Networks::new_with_refreshed_list(), Instant::now())),
+        }
+    }
+}
+
+impl NetworkSampler {
+    // refreshes the interfaces and hands them to `f` with the seconds since the previous refresh
+    pub fn with_networks<R>(&self, f: impl FnOnce(&Networks, f64) -> R) -> R {
+        let mut inner = self.inner.lock().unwrap();
+        let (networks, last) = &mut *inner;
+        networks.refresh(true);
+        let elapsed = last.elapsed().as_secs_f64().max(0.001);
+        *last = Instant::now();
+        f(networks, elapsed)
+    }
+
+    pub fn sample(&self) -> Vec<InterfaceInfo> {
+        self.with_networks(interfaces)
+    }
+}
+
+fn interfaces(networks: &Networks, elapsed: f64) -> Vec<InterfaceInfo> {
+    let mut interfaces: Vec<InterfaceInfo> = networks
+        .iter()
+        .map(|(name, data)| InterfaceInfo {
+            name: name.clone(),
+            mac: data.mac_address().to_string(),
+            addresses: data
+                .ip_networks()
+                .iter()
+                .map(|ip| format!("{}/{}", ip.addr, ip.prefix))
+                .collect(),
+            mtu: data.mtu(),
+            is_virtual: is_virtual(name),
+            received: (data.received() as f64 / elapsed) as u64,
+            transmitted: (data.transmitted() as f64 / elapsed) as u64,
+            total_received: data.total_received(),
+            total_transmitted: data.total_transmitted(),
+            total_packets_received: data.total_packets_received(),
+            total_packets_transmitted: data.total_packets_transmitted(),
+            total_errors_on_received: data.total_errors_on_received(),
+            total_errors_on_transmitted: data.total_errors_on_transmitted(),
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
+
+// loopback, bridges, veth pairs and tunnels have no backing device
+fn is_virtual(name: &str) -> bool {
+    if cfg!(target_os = "linux") {
+        Path::new("/sys/devices/virtual/net").join(name).exists()
+    } else {
+        name.starts_with("lo")
+    }
+}
@@ -0,0 +1,238 @@
+use serde::Serialize;
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::net::{Ipv4Addr, Ipv6Addr};
+#[cfg(target_os = "linux")]
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, Uid, Users};
+
+#[derive(Serialize)]
+pub struct PortInfo {
+    pub protocol: String,
+    pub address: String,
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub name: String,
+    pub user: String,
+}
+
+// tcp state LISTEN, udp sockets that are bound but not connected show as CLOSE
+#[cfg(target_os = "linux")]
+const TCP_LISTEN: &str = "0A";
+#[cfg(target_os = "linux")]
+const UDP_UNCONNECTED: &str = "07";
+
+#[cfg(target_os = "linux")]
+struct Socket {
+    protocol: &'static str,
+    address: String,
+    port: u16,
+    uid: String,
+    inode: u64,
+}
+
+#[cfg(target_os = "linux")]
+pub fn listening_ports() -> Vec<PortInfo> {
+    let mut sockets = Vec::new();
+    for (protocol, file, state) in [
+        ("tcp", "/proc/net/tcp", TCP_LISTEN),
+        ("tcp6", "/proc/net/tcp6", TCP_LISTEN),
+        ("udp", "/proc/net/udp", UDP_UNCONNECTED),
+        ("udp6", "/proc/net/udp6", UDP_UNCONNECTED),
+    ] {
+        if let Ok(content) = fs::read_to_string(file) {
+            sockets.extend(parse_sockets(protocol, &content, state));
+        }
+    }
+
+    let owners = socket_owners();
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+    let users = Users::new_with_refreshed_list();
+
+    let mut ports: Vec<PortInfo> = sockets
+        .into_iter()
+        .map(|socket| {
+            let pid = owners.get(&socket.inode).copied();
+            let name = pid
+                .and_then(|pid| sys.process(Pid::from_u32(pid)))
+                .map(|process| process.name().to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let user = socket
+                .uid
+                .parse::<Uid>()
+                .ok()
+                .and_then(|uid| users.get_user_by_id(&uid))
+                .map(|user| user.name().to_string())
+                .unwrap_or(socket.uid);
+            PortInfo {
+                protocol: socket.protocol.to_string(),
+                address: socket.address,
+                port: socket.port,
+                pid,
+                name,
+                user,
+            }
+        })
+        .collect();
+    ports.sort_by(|a, b| a.port.cmp(&b.port).then(a.protocol.cmp(&b.protocol)));
+    ports
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn listening_ports() -> Vec<PortInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn parse_sockets(protocol: &'static str, content: &str, state: &str) -> Vec<Socket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != state {
+                return None;
+            }
+            let (address, port) = fields[1].split_once(':')?;
+            Some(Socket {
+                protocol,
+                address: parse_address(address)?,
+                port: u16::from_str_radix(port, 16).ok()?,
+                uid: fields[7].to_string(),
+                inode: fields[9].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// addresses are printed as native-endian 32 bit words of the network-order bytes
+#[cfg(target_os = "linux")]
+fn parse_address(hex: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(16);
+    for i in (0..hex.len()).step_by(8) {
+        let word = u32::from_str_radix(hex.get(i..i + 8)?, 16).ok()?;
+        bytes.extend_from_slice(&word.to_ne_bytes());
+    }
+    match bytes.len() {
+        4 => Some(Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]).to_string()),
+        16 => {
+            let octets: [u8; 16] = bytes.try_into().ok()?;
+            Some(Ipv6Addr::from(octets).to_string())
+        }
+        _ => None,
+    }
+}
+
+// maps socket inodes to the pid holding them, only processes we may inspect are found
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return owners;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if let Ok(target) = fs::read_link(fd.path()) {
+                let target = target.to_string_lossy();
+                if let Some(inode) = target
+                    .strip_prefix("socket:[")
+                    .and_then(|s| s.strip_suffix(']'))
+                    .and_then(|s| s.parse::<u64>().ok())
+                {
+                    owners.insert(inode, pid);
+                }
+            }
+        }
+    }
+    owners
+}
+
+// the lines are what a little-endian kernel prints
+#[cfg(all(test, target_os = "linux", target_endian = "little"))]
+mod tests {
+    use super::*;
+
+    const TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 12345 1 0000000000000000 100 0 0 10 0
+   1: 0A01A8C0:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 23456 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:A2B4 0100007F:0CEA 01 00000000:00000000 00:00000000 00000000  1000        0 34567 1 0000000000000000 20 4 30 10 -1
+";
+
+    const UDP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  0: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 45678 2 0000000000000000 0
+  1: 000080FE000000000000000001000000:0222 000080FE000000000000000002000000:0222 01 00000000:00000000 00:00000000 00000000     0        0 56789 2 0000000000000000 0
+";
+
+    #[test]
+    fn parses_addresses() {
+        for (hex, address) in [
+            ("0100007F", Some("127.0.0.1")),
+            ("0A01A8C0", Some("192.168.1.10")),
+            ("00000000", Some("0.0.0.0")),
+            ("00000000000000000000000001000000", Some("::1")),
+            ("000080FE000000000000000001000000", Some("fe80::1")),
+            ("00000000000000000000000000000000", Some("::")),
+            // neither 4 nor 16 bytes
+            ("", None),
+            ("0100007F0100007F", None),
+            ("0100007", None),
+            ("0100007G", None),
+        ] {
+            assert_eq!(parse_address(hex).as_deref(), address, "{}", hex);
+        }
+    }
+
+    #[test]
+    fn keeps_listening_tcp_sockets() {
+        let sockets = parse_sockets("tcp", TCP, TCP_LISTEN);
+        let parsed: Vec<(&str, u16, &str, u64)> = sockets
+            .iter()
+            .map(|socket| {
+                (
+                    socket.address.as_str(),
+                    socket.port,
+                    socket.uid.as_str(),
+                    socket.inode,
+                )
+            })
+            .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                ("127.0.0.1", 3306, "999", 12345),
+                ("192.168.1.10", 8080, "1000", 23456),
+            ]
+        );
+        assert!(sockets.iter().all(|socket| socket.protocol == "tcp"));
+    }
+
+    #[test]
+    fn keeps_unconnected_udp_sockets() {
+        let sockets = parse_sockets("udp6", UDP6, UDP_UNCONNECTED);
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].address, "::1");
+        assert_eq!(sockets[0].port, 53);
+        assert_eq!(sockets[0].inode, 45678);
+    }
+
+    #[test]
+    fn skips_short_and_malformed_lines() {
+        let content = "header\n   0: 0100007F:0CEA 00000000:0000 0A\n   1: 0100007F:ZZZZ 00000000:0000 0A 0 0 0 0 0 1\n";
+        assert!(parse_sockets("tcp", content, TCP_LISTEN).is_empty());
+    }
+}
@@ -0,0 +1,215 @@
+// what every running process did since the app first saw it, kept in memory only;
+// once a process has too many points, neighbours are averaged into one
+use serde::Serialize;
+use std::collections::HashMap;
+use sysinfo::{Pid, System};
+
+// points kept per process, the resolution halves whenever this is reached
+const MAX_POINTS: usize = 360;
+
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct ProcessPoint {
+    // milliseconds since the epoch
+    pub timestamp: u64,
+    // percent of one core
+    pub cpu: f32,
+    // bytes
+    pub memory: u64,
+    // bytes per second
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub threads: usize,
+}
+
+// the samples not yet averaged into a point, summed up
+#[derive(Default)]
+struct Pending {
+    sum: ProcessPoint,
+    count: usize,
+}
+
+impl Pending {
+    fn add(&mut self, point: ProcessPoint) {
+        self.sum.timestamp = point.timestamp;
+        self.sum.cpu += point.cpu;
+        self.sum.memory += point.memory;
+        self.sum.disk_read += point.disk_read;
+        self.sum.disk_write += point.disk_write;
+        self.sum.threads += point.threads;
+        self.count += 1;
+    }
+
+    // stamped with the time of the newest sample
+    fn average(&self) -> Option<ProcessPoint> {
+        let n = self.count;
+        (n > 0).then(|| ProcessPoint {
+            timestamp: self.sum.timestamp,
+            cpu: self.sum.cpu / n as f32,
+            memory: self.sum.memory / n as u64,
+            disk_read: self.sum.disk_read / n as u64,
+            disk_write: self.sum.disk_write / n as u64,
+            threads: self.sum.threads / n,
+        })
+    }
+}
+
+struct Lifetime {
+    points: Vec<ProcessPoint>,
+    // samples averaged into one point
+    every: usize,
+    pending: Pending,
+    // disk totals at the previous sample, for the rates
+    read: u64,
+    written: u64,
+}
+
+impl Lifetime {
+    fn new(read: u64, written: u64) -> Self {
+        Lifetime {
+            points: Vec::new(),
+            every: 1,
+            pending: Pending::default(),
+            read,
+            written,
+        }
+    }
+
+    // averages `every` samples into a point, halves the resolution once it is full
+    fn push(&mut self, point: ProcessPoint) {
+        self.pending.add(point);
+        if self.pending.count < self.every {
+            return;
+        }
+        self.points.extend(self.pending.average());
+        self.pending = Pending::default();
+        if self.points.len() >= MAX_POINTS {
+            self.points = self
+                .points
+                .chunks(2)
+                .filter_map(|pair| {
+                    let mut merged = Pending::default();
+                    pair.iter().for_each(|point| merged.add(*point));
+                    merged.average()
+                })
+                .collect();
+            self.every *= 2;
+        }
+    }
+
+    fn points(&self) -> Vec<ProcessPoint> {
+        let mut points = self.points.clone();
+        points.extend(self.pending.average());
+        points
+    }
+}
+
+#[derive(Default)]
+pub struct ProcessHistories {
+    processes: HashMap<(u32, u64), Lifetime>,
+    last: Option<u64>,
+}
+
+impl ProcessHistories {
+    pub fn update(&mut self, sys: &System, now: u64) {
+        let elapsed = self
+            .last
+            .map_or(0.0, |last| now.saturating_sub(last) as f64 / 1000.0);
+        self.last = Some(now);
+        let processes = sys.processes();
+        self.processes.retain(|(pid, start_time), _| {
+            processes
+                .get(&Pid::from_u32(*pid))
+                .is_some_and(|process| process.start_time() == *start_time)
+        });
+        for (pid, process) in processes {
+            if process.thread_kind().is_some() {
+                continue;
+            }
+            let usage = process.disk_usage();
+            let lifetime = self
+                .processes
+                .entry((pid.as_u32(), process.start_time()))
+                .or_insert_with(|| {
+                    Lifetime::new(usage.total_read_bytes, usage.total_written_bytes)
+                });
+            let rate = |total: u64, previous: u64| {
+                if elapsed > 0.0 {
+                    (total.saturating_sub(previous) as f64 / elapsed) as u64
+                } else {
+                    0
+                }
+            };
+            lifetime.push(ProcessPoint {
+                timestamp: now,
+                cpu: process.cpu_usage(),
+                memory: process.memory(),
+                disk_read: rate(usage.total_read_bytes, lifetime.read),
+                disk_write: rate(usage.total_written_bytes, lifetime.written),
+                threads: process.tasks().map(|tasks| tasks.len() + 1).unwrap_or(1),
+            });
+            lifetime.read = usage.total_read_bytes;
+            lifetime.written = usage.total_written_bytes;
+        }
+    }
+
+    // oldest first, empty for a process that is gone or was never seen
+    pub fn get(&self, pid: u32, start_time: u64) -> Vec<ProcessPoint> {
+        self.processes
+            .get(&(pid, start_time))
+            .map_or_else(Vec::new, Lifetime::points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(i: usize) -> ProcessPoint {
+        ProcessPoint {
+            timestamp: i as u64 * 1000,
+            cpu: i as f32,
+            memory: i as u64 * 10,
+            disk_read: 0,
+            disk_write: 0,
+            threads: 1,
+        }
+    }
+
+    #[test]
+    fn keeps_every_sample_until_full() {
+        let mut lifetime = Lifetime::new(0, 0);
+        (0..MAX_POINTS - 1).for_each(|i| lifetime.push(point(i)));
+        let points = lifetime.points();
+        assert_eq!(points.len(), MAX_POINTS - 1);
+        assert_eq!(points[10].cpu, 10.0);
+    }
+
+    #[test]
+    fn halves_the_resolution_when_full() {
+        let mut lifetime = Lifetime::new(0, 0);
+        (0..MAX_POINTS).for_each(|i| lifetime.push(point(i)));
+        let points = lifetime.points();
+        assert_eq!(points.len(), MAX_POINTS / 2);
+        assert_eq!(lifetime.every, 2);
+        // neighbours averaged, stamped with the newer one
+        assert_eq!(points[0].cpu, 0.5);
+        assert_eq!(points[0].memory, 5);
+        assert_eq!(points[0].timestamp, 1000);
+        assert_eq!(points[1].cpu, 2.5);
+
+        // from now on two samples make a point, a lone one shows as pending
+        lifetime.push(point(MAX_POINTS));
+        assert_eq!(lifetime.points.len(), MAX_POINTS / 2);
+        let points = lifetime.points();
+        assert_eq!(points.len(), MAX_POINTS / 2 + 1);
+        assert_eq!(points.last().unwrap().cpu, MAX_POINTS as f32);
+        lifetime.push(point(MAX_POINTS + 1));
+        assert_eq!(lifetime.points.len(), MAX_POINTS / 2 + 1);
+        assert_eq!(lifetime.points.last().unwrap().cpu, MAX_POINTS as f32 + 0.5);
+    }
+
+    #[test]
+    fn unknown_processes_have_no_points() {
+        assert!(ProcessHistories::default().get(1, 1).is_empty());
+    }
+}
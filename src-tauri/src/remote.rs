@@ -0,0 +1,198 @@
+// websocket connection to an agent, requests from any thread are matched to their replies by id
+use crate::agent::{AgentReply, AgentRequest};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::handshake::HandshakeError;
+use tungstenite::http::header::AUTHORIZATION;
+use tungstenite::http::Uri;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// an unreachable host should not keep the hosts page waiting for the os to give up
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// a disk scan node or a process list can take a moment on a busy host
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub type EventSink = Arc<dyn Fn(&str, Value) + Send + Sync>;
+
+type Reply = Result<Result<Value, Value>, RequestError>;
+type Pending = (AgentRequest, Sender<Reply>);
+
+pub enum RequestError {
+    // the connection was gone before the request was written, so sending it again is safe
+    Unsent,
+    // the agent may have run it, a kill or an unmount must not be sent twice
+    Lost,
+    TimedOut,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            RequestError::Unsent => "disconnected",
+            RequestError::Lost => "the connection to the agent was lost",
+            RequestError::TimedOut => "the agent did not answer",
+        })
+    }
+}
+
+pub struct RemoteClient {
+    requests: Sender<Pending>,
+    next_id: AtomicU64,
+    timeout: Duration,
+}
+
+impl RemoteClient {
+    pub fn connect(url: &str, token: &str, on_event: EventSink) -> Result<Self, String> {
+        let mut request = url.into_client_request().map_err(|err| err.to_string())?;
+        let authorization = format!("Bearer {}", token)
+            .parse()
+            .map_err(|_| "the token is not a valid header value".to_string())?;
+        request.headers_mut().insert(AUTHORIZATION, authorization);
+        let stream = open(request.uri()).map_err(|err| err.to_string())?;
+        // the same socket under tls, to change its timeout after the handshake
+        let tcp = stream.try_clone().map_err(|err| err.to_string())?;
+        // ws:// stays plain, wss:// is checked against the usual root certificates
+        let (socket, _) = tungstenite::client_tls_with_config(request, stream, None, None)
+            .map_err(|err| match err {
+                HandshakeError::Failure(tungstenite::Error::Http(response))
+                    if response.status().as_u16() == 401 =>
+                {
+                    "the agent rejected the token".to_string()
+                }
+                HandshakeError::Failure(err) => err.to_string(),
+                HandshakeError::Interrupted(_) => "the agent did not answer".to_string(),
+            })?;
+        tcp.set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| err.to_string())?;
+        let (requests, receiver) = mpsc::channel();
+        thread::spawn(move || run(socket, receiver, on_event));
+        Ok(RemoteClient {
+            requests,
+            next_id: AtomicU64::new(1),
+            timeout: REQUEST_TIMEOUT,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        RemoteClient { timeout, ..self }
+    }
+
+    // Err when there is no answer, the inner result is the command's own
+    pub fn request(&self, command: &str, args: Value) -> Reply {
+        let (sender, reply) = mpsc::channel();
+        let request = AgentRequest {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            command: command.to_string(),
+            args,
+        };
+        self.requests
+            .send((request, sender))
+            .map_err(|_| RequestError::Unsent)?;
+        match reply.recv_timeout(self.timeout) {
+            Ok(reply) => reply,
+            Err(RecvTimeoutError::Timeout) => Err(RequestError::TimedOut),
+            Err(RecvTimeoutError::Disconnected) => Err(RequestError::Lost),
+        }
+    }
+}
+
+// the handshake gets as long as the connection to answer
+fn open(uri: &Uri) -> io::Result<TcpStream> {
+    let host = uri
+        .host()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the url has no host"))?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = uri
+        .port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("wss") {
+            443
+        } else {
+            80
+        });
+    let mut last = io::Error::new(io::ErrorKind::NotFound, "the host could not be resolved");
+    for address in (host, port).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+                stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+                return Ok(stream);
+            }
+            Err(err) => last = err,
+        }
+    }
+    Err(last)
+}
+
+// owns the socket until the client is dropped or the connection breaks,
+// dropping the pending senders then fails the waiting requests
+fn run(
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    requests: Receiver<Pending>,
+    on_event: EventSink,
+) {
+    talk(socket, &requests, on_event);
+    // requests still queued never reached the agent
+    while let Ok((_, reply)) = requests.try_recv() {
+        let _ = reply.send(Err(RequestError::Unsent));
+    }
+}
+
+fn talk(
+    mut socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    requests: &Receiver<Pending>,
+    on_event: EventSink,
+) {
+    let mut pending: HashMap<u64, Sender<Reply>> = HashMap::new();
+    loop {
+        loop {
+            match requests.try_recv() {
+                Ok((request, reply)) => {
+                    let Ok(text) = serde_json::to_string(&request) else {
+                        continue;
+                    };
+                    if socket.send(Message::text(text)).is_err() {
+                        let _ = reply.send(Err(RequestError::Unsent));
+                        return;
+                    }
+                    pending.insert(request.id, reply);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    return;
+                }
+            }
+        }
+        match socket.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str::<AgentReply>(&text) {
+                Ok(AgentReply::Response { id, result }) => {
+                    if let Some(reply) = pending.remove(&id) {
+                        let _ = reply.send(Ok(result));
+                    }
+                }
+                Ok(AgentReply::Event { event, payload }) => on_event(&event, payload),
+                Err(_) => {}
+            },
+            Ok(Message::Close(_)) => return,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(_) => return,
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::mounts::{self, DiskSampler};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+pub struct BlockingProcess {
+    pub pid: u32,
+    pub name: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UnmountError {
+    NotRemovable,
+    NotMounted,
+    Busy { processes: Vec<BlockingProcess> },
+    Failed { message: String },
+}
+
+// only a handful of paths per process, enough to recognise what is open
+const PATHS_PER_PROCESS: usize = 5;
+
+pub fn unmount(mount_point: &Path) -> Result<(), UnmountError> {
+    let processes = blocking_processes(mount_point);
+    if !processes.is_empty() {
+        return Err(UnmountError::Busy { processes });
+    }
+    umount(mount_point)
+}
+
+// unmount for a mount point of a known removable disk only
+pub fn unmount_removable(disks: &DiskSampler, mount_point: &Path) -> Result<(), UnmountError> {
+    let removable = disks.with_disks(|disks| {
+        disks
+            .iter()
+            .find(|disk| disk.mount_point() == mount_point)
+            .map(|disk| disk.is_removable())
+    });
+    match removable {
+        None => Err(UnmountError::NotMounted),
+        Some(false) => Err(UnmountError::NotRemovable),
+        Some(true) => unmount(mount_point),
+    }
+}
+
+pub fn eject_removable(disks: &DiskSampler, device: &str) -> Result<(), UnmountError> {
+    let removable = disks.with_disks(|disks| {
+        disks
+            .iter()
+            .any(|disk| disk.name() == device && disk.is_removable())
+    });
+    if !removable {
+        return Err(UnmountError::NotRemovable);
+    }
+    eject(device)
+}
+
+// unmounts every partition of the device, then powers it off
+pub fn eject(device: &str) -> Result<(), UnmountError> {
+    let base = base_device(device);
+    let mounted: Vec<PathBuf> = mounts::mounts()
+        .into_iter()
+        .filter(|mount| base_device(&mount.device) == base)
+        .map(|mount| PathBuf::from(mount.mount_point))
+        .collect();
+
+    let processes: Vec<BlockingProcess> = mounted
+        .iter()
+        .flat_map(|mount_point| blocking_processes(mount_point))
+        .collect();
+    if !processes.is_empty() {
+        return Err(UnmountError::Busy { processes });
+    }
+    for mount_point in &mounted {
+        umount(mount_point)?;
+    }
+    power_off(&base)
+}
+
+#[cfg(target_os = "linux")]
+fn umount(mount_point: &Path) -> Result<(), UnmountError> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(mount_point.as_os_str().as_bytes())
+        .map_err(|_| UnmountError::NotMounted)?;
+    if unsafe { libc::umount2(path.as_ptr(), 0) } == 0 {
+        return Ok(());
+    }
+    let error = std::io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::EINVAL) | Some(libc::ENOENT) => Err(UnmountError::NotMounted),
+        _ => Err(UnmountError::Failed {
+            message: error.to_string(),
+        }),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn umount(_mount_point: &Path) -> Result<(), UnmountError> {
+    Err(UnmountError::Failed {
+        message: "unmounting is only supported on Linux".to_string(),
+    })
+}
+
+// optical drives get a tray eject, everything else is detached from the scsi bus
+#[cfg(target_os = "linux")]
+fn power_off(base: &str) -> Result<(), UnmountError> {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    const CDROMEJECT: libc::c_ulong = 0x5309;
+
+    let failed = |error: std::io::Error| UnmountError::Failed {
+        message: error.to_string(),
+    };
+    unsafe { libc::sync() };
+    if base.starts_with("sr") {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(Path::new("/dev").join(base))
+            .map_err(failed)?;
+        if unsafe { libc::ioctl(file.as_raw_fd(), CDROMEJECT as _) } != 0 {
+            return Err(failed(std::io::Error::last_os_error()));
+        }
+        return Ok(());
+    }
+    fs::write(
+        Path::new("/sys/block").join(base).join("device/delete"),
+        "1",
+    )
+    .map_err(failed)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn power_off(_base: &str) -> Result<(), UnmountError> {
+    Err(UnmountError::Failed {
+        message: "ejecting is only supported on Linux".to_string(),
+    })
+}
+
+// "/dev/sdb1" -> "sdb", partitions link to their parent disk in /sys/class/block
+fn base_device(device: &str) -> String {
+    let name = device.trim_start_matches("/dev/");
+    let class = Path::new("/sys/class/block").join(name);
+    if class.join("partition").exists() {
+        if let Some(parent) = fs::canonicalize(&class).ok().and_then(|path| {
+            path.parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+        }) {
+            return parent;
+        }
+    }
+    name.to_string()
+}
+
+// processes with an open file, working directory or root below the mount point
+pub fn blocking_processes(mount_point: &Path) -> Vec<BlockingProcess> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let own_pid = std::process::id();
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            if pid == own_pid {
+                return None;
+            }
+            let proc_dir = entry.path();
+            let mut paths: Vec<String> = ["cwd", "root"]
+                .iter()
+                .filter_map(|link| fs::read_link(proc_dir.join(link)).ok())
+                .chain(
+                    fs::read_dir(proc_dir.join("fd"))
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .filter_map(|fd| fs::read_link(fd.path()).ok()),
+                )
+                .filter(|path| path.starts_with(mount_point))
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            if paths.is_empty() {
+                return None;
+            }
+            paths.sort();
+            paths.dedup();
+            paths.truncate(PATHS_PER_PROCESS);
+            let name = fs::read_to_string(proc_dir.join("comm"))
+                .map(|comm| comm.trim().to_string())
+                .unwrap_or_default();
+            Some(BlockingProcess { pid, name, paths })
+        })
+        .collect()
+}
@@ -0,0 +1,288 @@
+use crate::columns::Column;
+use crate::history::now_millis;
+use crate::process_history::{ProcessHistories, ProcessPoint};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::{
+    CpuRefreshKind, MemoryRefreshKind, Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind,
+    System, UpdateKind,
+};
+
+// samples kept per process for the sparklines, at most one per interval,
+// the lifetime histories are sampled at the same pace
+const TREND_SAMPLES: usize = 60;
+const TREND_INTERVAL: Duration = Duration::from_secs(1);
+
+// the last samples of one process, oldest first
+pub struct Trend {
+    // tells a reused pid apart
+    start_time: u64,
+    pub cpu: VecDeque<f32>,
+    // bytes
+    pub memory: VecDeque<u64>,
+    // bytes per second since the previous sample
+    pub read_rate: u64,
+    pub write_rate: u64,
+    read: u64,
+    written: u64,
+}
+
+pub type Trends = HashMap<Pid, Trend>;
+
+// cpu seconds a process used over the refreshes of the sampler, summed from the
+// usage of each refresh since sysinfo does not give the cumulative time
+#[derive(Clone, Copy)]
+struct CpuTime {
+    start_time: u64,
+    seconds: f64,
+}
+
+type CpuTimes = HashMap<Pid, CpuTime>;
+
+// cpu usage of every process from one poll of a processes table to the next, each
+// client has its own so tables with different refresh rates leave each other alone
+pub struct CpuWindow {
+    // when the sampler refreshed the times of the previous poll
+    at: Instant,
+    times: CpuTimes,
+    usage: HashMap<Pid, f32>,
+}
+
+impl CpuWindow {
+    fn new(times: &CpuTimes, at: Instant) -> Self {
+        CpuWindow {
+            at,
+            times: times.clone(),
+            usage: HashMap::new(),
+        }
+    }
+
+    // a poll right after the last one, like a changed sort, keeps the figures it had
+    fn poll(&mut self, times: &CpuTimes, at: Instant) {
+        let elapsed = at.duration_since(self.at);
+        if elapsed < sysinfo::MINIMUM_CPU_UPDATE_INTERVAL {
+            return;
+        }
+        let seconds = elapsed.as_secs_f64();
+        self.usage = times
+            .iter()
+            .map(|(pid, time)| {
+                let previous = self
+                    .times
+                    .get(pid)
+                    .filter(|previous| previous.start_time == time.start_time)
+                    .map_or(0.0, |previous| previous.seconds);
+                (*pid, ((time.seconds - previous) / seconds * 100.0) as f32)
+            })
+            .collect();
+        self.times = times.clone();
+        self.at = at;
+    }
+
+    // None until the table polled twice
+    pub fn usage(&self, pid: Pid) -> Option<f32> {
+        self.usage.get(&pid).copied()
+    }
+}
+
+// read once per process, and only while a shown column needs them
+#[derive(Default, Clone, Copy, PartialEq)]
+struct Details {
+    user: bool,
+    cmd: bool,
+    exe: bool,
+}
+
+impl Details {
+    fn of(columns: &[Column]) -> Self {
+        Details {
+            user: columns.contains(&Column::User),
+            cmd: columns.contains(&Column::CommandLine),
+            exe: columns.contains(&Column::Exe),
+        }
+    }
+
+    fn includes(self, other: Details) -> bool {
+        (self.user || !other.user) && (self.cmd || !other.cmd) && (self.exe || !other.exe)
+    }
+
+    fn kind(self) -> ProcessRefreshKind {
+        let update = |wanted: bool| {
+            if wanted {
+                UpdateKind::OnlyIfNotSet
+            } else {
+                UpdateKind::Never
+            }
+        };
+        ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory()
+            .with_disk_usage()
+            .with_user(update(self.user))
+            .with_cmd(update(self.cmd))
+            .with_exe(update(self.exe))
+    }
+}
+
+struct Inner {
+    sys: System,
+    refreshed: Instant,
+    trends: Trends,
+    trended: Option<Instant>,
+    histories: ProcessHistories,
+    details: Details,
+    cpu_times: CpuTimes,
+    windows: HashMap<u64, CpuWindow>,
+}
+
+// one System shared by the commands, the history recorder and the exporter,
+// cpu usage needs two refreshes of the same instance to mean anything
+pub struct SystemSampler {
+    inner: Mutex<Inner>,
+}
+
+impl Default for SystemSampler {
+    fn default() -> Self {
+        SystemSampler {
+            inner: Mutex::new(Inner {
+                sys: System::new_with_specifics(
+                    RefreshKind::nothing()
+                        .with_cpu(CpuRefreshKind::everything())
+                        .with_memory(MemoryRefreshKind::everything())
+                        .with_processes(Details::default().kind()),
+                ),
+                refreshed: Instant::now(),
+                trends: HashMap::new(),
+                trended: None,
+                histories: ProcessHistories::default(),
+                details: Details::default(),
+                cpu_times: HashMap::new(),
+                windows: HashMap::new(),
+            }),
+        }
+    }
+}
+
+impl SystemSampler {
+    // refreshes cpu, memory and processes unless that just happened, then hands them to `f`
+    pub fn with_system<R>(&self, f: impl FnOnce(&System) -> R) -> R {
+        self.sample(None, |inner| f(&inner.sys))
+    }
+
+    // like with_system, with the recent samples of every process, what `columns` need
+    // and the cpu usage since `client`'s processes table last polled
+    pub fn with_trends<R>(
+        &self,
+        columns: &[Column],
+        client: u64,
+        f: impl FnOnce(&System, &Trends, &CpuWindow) -> R,
+    ) -> R {
+        self.sample(Some(Details::of(columns)), |inner| {
+            let window = inner
+                .windows
+                .entry(client)
+                .or_insert_with(|| CpuWindow::new(&inner.cpu_times, inner.refreshed));
+            window.poll(&inner.cpu_times, inner.refreshed);
+            f(&inner.sys, &inner.trends, window)
+        })
+    }
+
+    // a client that went away
+    pub fn forget(&self, client: u64) {
+        self.inner.lock().unwrap().windows.remove(&client);
+    }
+
+    // `details` is None for callers other than the processes table, they leave its
+    // columns as they are
+    fn sample<R>(&self, details: Option<Details>, f: impl FnOnce(&mut Inner) -> R) -> R {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        // a column just shown is filled by refreshing early, refreshing only its fields
+        // would move the cpu usage baseline all the same
+        let missing = details.is_some_and(|details| !inner.details.includes(details));
+        if let Some(details) = details {
+            inner.details = details;
+        }
+        let elapsed = inner.refreshed.elapsed();
+        if missing || elapsed >= sysinfo::MINIMUM_CPU_UPDATE_INTERVAL {
+            inner.sys.refresh_cpu_usage();
+            inner.sys.refresh_memory();
+            inner.sys.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                inner.details.kind(),
+            );
+            inner.refreshed = Instant::now();
+            update_cpu_times(&mut inner.cpu_times, &inner.sys, elapsed);
+            let since_trend = inner.trended.map(|trended| trended.elapsed());
+            if since_trend.is_none_or(|elapsed| elapsed >= TREND_INTERVAL) {
+                update_trends(&mut inner.trends, &inner.sys, since_trend);
+                inner.histories.update(&inner.sys, now_millis());
+                inner.trended = Some(Instant::now());
+            }
+        }
+        f(inner)
+    }
+
+    pub fn process_history(&self, pid: u32, start_time: u64) -> Vec<ProcessPoint> {
+        self.inner.lock().unwrap().histories.get(pid, start_time)
+    }
+}
+
+// the usage of a refresh is the average since the previous one
+fn update_cpu_times(times: &mut CpuTimes, sys: &System, elapsed: Duration) {
+    let processes = sys.processes();
+    times.retain(|pid, time| {
+        processes
+            .get(pid)
+            .is_some_and(|process| process.start_time() == time.start_time)
+    });
+    let seconds = elapsed.as_secs_f64();
+    for (pid, process) in processes {
+        let time = times.entry(*pid).or_insert(CpuTime {
+            start_time: process.start_time(),
+            seconds: 0.0,
+        });
+        time.seconds += process.cpu_usage() as f64 / 100.0 * seconds;
+    }
+}
+
+fn update_trends(trends: &mut Trends, sys: &System, elapsed: Option<Duration>) {
+    let processes = sys.processes();
+    trends.retain(|pid, trend| {
+        processes
+            .get(pid)
+            .is_some_and(|process| process.start_time() == trend.start_time)
+    });
+    let seconds = elapsed.map_or(0.0, |elapsed| elapsed.as_secs_f64());
+    for (pid, process) in processes {
+        let usage = process.disk_usage();
+        let trend = trends.entry(*pid).or_insert_with(|| Trend {
+            start_time: process.start_time(),
+            cpu: VecDeque::with_capacity(TREND_SAMPLES),
+            memory: VecDeque::with_capacity(TREND_SAMPLES),
+            read_rate: 0,
+            write_rate: 0,
+            read: usage.total_read_bytes,
+            written: usage.total_written_bytes,
+        });
+        let rate = |total: u64, previous: u64| {
+            if seconds > 0.0 {
+                (total.saturating_sub(previous) as f64 / seconds) as u64
+            } else {
+                0
+            }
+        };
+        trend.read_rate = rate(usage.total_read_bytes, trend.read);
+        trend.write_rate = rate(usage.total_written_bytes, trend.written);
+        trend.read = usage.total_read_bytes;
+        trend.written = usage.total_written_bytes;
+        if trend.cpu.len() == TREND_SAMPLES {
+            trend.cpu.pop_front();
+            trend.memory.pop_front();
+        }
+        trend.cpu.push_back(process.cpu_usage());
+        trend.memory.push_back(process.memory());
+    }
+}
@@ -0,0 +1,396 @@
+// preferences that outlive a restart, one versioned json file in the config dir
+use crate::columns::{self, Column, DEFAULT_COLUMNS};
+use crate::exporter::ExporterConfig;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// bumped with every migration below
+pub const VERSION: u32 = 1;
+// milliseconds between refreshes of the processes table
+pub const MIN_REFRESH_INTERVAL: u64 = 500;
+pub const MAX_REFRESH_INTERVAL: u64 = 10 * 1000;
+const DEFAULT_REFRESH_INTERVAL: u64 = 1000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort {
+            column: Column::Name,
+            descending: false,
+        }
+    }
+}
+
+// logical pixels, restored when the app starts
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WindowSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+// missing fields take their defaults, so older files load as they are
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub refresh_interval: u64,
+    // the processes table holds still under the pointer or with a row selected
+    pub freeze_on_hover: bool,
+    pub sort: Sort,
+    pub filter: Option<String>,
+    pub window: Option<WindowSize>,
+    pub columns: Vec<Column>,
+    pub exporter: ExporterConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: VERSION,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            freeze_on_hover: false,
+            sort: Sort::default(),
+            filter: None,
+            window: None,
+            columns: DEFAULT_COLUMNS.to_vec(),
+            exporter: ExporterConfig::default(),
+        }
+    }
+}
+
+impl Settings {
+    // keeps what the ui sends within what the app can do
+    fn normalized(mut self) -> Self {
+        self.version = VERSION;
+        self.refresh_interval = self
+            .refresh_interval
+            .clamp(MIN_REFRESH_INTERVAL, MAX_REFRESH_INTERVAL);
+        self.filter = self.filter.filter(|filter| !filter.is_empty());
+        self.columns = columns::normalized(self.columns);
+        self
+    }
+}
+
+type Migration = fn(&mut Map<String, Value>, &Path);
+
+// MIGRATIONS[n] takes a file of version n to n + 1
+const MIGRATIONS: [Migration; VERSION as usize] = [absorb_separate_files];
+
+// before version 1 the columns and the exporter had files of their own
+fn absorb_separate_files(settings: &mut Map<String, Value>, dir: &Path) {
+    for (key, file) in [("columns", "columns.json"), ("exporter", "exporter.json")] {
+        if let Some(value) = fs::read(dir.join(file))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+        {
+            settings.insert(key.to_string(), value);
+        }
+    }
+}
+
+fn version(settings: &Map<String, Value>) -> u64 {
+    settings.get("version").and_then(Value::as_u64).unwrap_or(0)
+}
+
+fn migrate(mut settings: Map<String, Value>, dir: &Path) -> Map<String, Value> {
+    // a file from a newer app is read as far as it is understood
+    for migration in MIGRATIONS.iter().skip(version(&settings) as usize) {
+        migration(&mut settings, dir);
+    }
+    settings.insert("version".to_string(), VERSION.into());
+    settings
+}
+
+fn read<T: DeserializeOwned>(stored: &Map<String, Value>, key: &str, field: &mut T) {
+    if let Some(value) = stored.get(key).and_then(|value| T::deserialize(value).ok()) {
+        *field = value;
+    }
+}
+
+fn from_fields(stored: &Map<String, Value>) -> Settings {
+    let mut settings = Settings::default();
+    read_fields(stored, &mut settings);
+    settings
+}
+
+// every field of Settings on its own, a bad or missing value leaves that field alone
+fn read_fields(stored: &Map<String, Value>, settings: &mut Settings) {
+    read(stored, "refresh_interval", &mut settings.refresh_interval);
+    read(stored, "freeze_on_hover", &mut settings.freeze_on_hover);
+    read(stored, "sort", &mut settings.sort);
+    read(stored, "filter", &mut settings.filter);
+    read(stored, "window", &mut settings.window);
+    read(stored, "exporter", &mut settings.exporter);
+    // columns this version does not know are dropped rather than the whole list
+    if let Some(Value::Array(stored)) = stored.get("columns") {
+        let known: Vec<Column> = stored
+            .iter()
+            .filter_map(|column| Column::deserialize(column).ok())
+            .collect();
+        if !known.is_empty() {
+            settings.columns = known;
+        }
+    }
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+    // a file that could neither be read nor backed up is never written over
+    protected: bool,
+}
+
+impl SettingsStore {
+    // no file yet counts as version 0, one that is not json is copied to settings.json.bak
+    // before the defaults take its place
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join("settings.json");
+        let (stored, protected) = match fs::read(&path) {
+            Ok(content) => match serde_json::from_slice(&content) {
+                Ok(stored) => (stored, false),
+                Err(_) => (
+                    Map::new(),
+                    fs::copy(&path, dir.join("settings.json.bak")).is_err(),
+                ),
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (Map::new(), false),
+            Err(_) => (Map::new(), true),
+        };
+        let outdated = version(&stored) < VERSION as u64;
+        let settings = from_fields(&migrate(stored, dir)).normalized();
+        let store = SettingsStore {
+            path,
+            settings: Mutex::new(settings.clone()),
+            protected,
+        };
+        // written back so the migrations run once
+        if outdated {
+            let _ = store.save(&settings);
+        }
+        store
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    // holds the lock from reading to writing, so concurrent changes all land,
+    // returns what was kept
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> io::Result<Settings> {
+        let mut current = self.settings.lock().unwrap();
+        let mut settings = current.clone();
+        change(&mut settings);
+        let settings = settings.normalized();
+        self.save(&settings)?;
+        *current = settings.clone();
+        Ok(settings)
+    }
+
+    // changes only the fields in `fields`, the exporter is left to set_exporter_config
+    // since it has to be started before it is saved
+    pub fn patch(&self, mut fields: Map<String, Value>) -> io::Result<Settings> {
+        fields.remove("exporter");
+        self.update(|settings| read_fields(&fields, settings))
+    }
+
+    fn save(&self, settings: &Settings) -> io::Result<()> {
+        if self.protected {
+            return Err(io::Error::other(format!(
+                "{} could not be read and is left as it is",
+                self.path.display()
+            )));
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_vec_pretty(settings)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // a fresh directory per test, tests run in parallel
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "proccessmanager-settings-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn stored(dir: &Path) -> Value {
+        serde_json::from_slice(&fs::read(dir.join("settings.json")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn version_0_absorbs_the_separate_files() {
+        let dir = dir("absorb");
+        fs::write(dir.join("columns.json"), r#"["pid", "cpu", "user"]"#).unwrap();
+        fs::write(
+            dir.join("exporter.json"),
+            r#"{"enabled": true, "port": 9999}"#,
+        )
+        .unwrap();
+        let settings = SettingsStore::load(&dir).get();
+        assert!(settings.columns == [Column::Name, Column::Pid, Column::Cpu, Column::User]);
+        assert!(
+            settings.exporter
+                == ExporterConfig {
+                    enabled: true,
+                    port: 9999
+                }
+        );
+        assert!(settings.refresh_interval == DEFAULT_REFRESH_INTERVAL);
+        // written back, so the migration does not run again
+        assert_eq!(stored(&dir)["version"], json!(VERSION));
+        assert_eq!(stored(&dir)["exporter"]["port"], json!(9999));
+    }
+
+    #[test]
+    fn version_0_without_files_gets_the_defaults() {
+        let settings = SettingsStore::load(&dir("defaults")).get();
+        assert!(settings == Settings::default());
+    }
+
+    #[test]
+    fn version_1_ignores_the_separate_files() {
+        let dir = dir("current");
+        fs::write(
+            dir.join("settings.json"),
+            r#"{"version": 1, "columns": ["name", "cpu"]}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("columns.json"), r#"["pid"]"#).unwrap();
+        let settings = SettingsStore::load(&dir).get();
+        assert!(settings.columns == [Column::Name, Column::Cpu]);
+    }
+
+    #[test]
+    fn normalized_keeps_values_in_range() {
+        let settings = Settings {
+            version: 0,
+            refresh_interval: 1,
+            filter: Some(String::new()),
+            columns: vec![Column::Cpu, Column::Cpu],
+            ..Settings::default()
+        }
+        .normalized();
+        assert_eq!(settings.version, VERSION);
+        assert_eq!(settings.refresh_interval, MIN_REFRESH_INTERVAL);
+        assert!(settings.filter.is_none());
+        assert!(settings.columns == [Column::Name, Column::Cpu]);
+        let slow = Settings {
+            refresh_interval: u64::MAX,
+            ..Settings::default()
+        };
+        assert_eq!(slow.normalized().refresh_interval, MAX_REFRESH_INTERVAL);
+    }
+
+    #[test]
+    fn a_bad_value_only_loses_itself() {
+        let dir = dir("fields");
+        let file = json!({
+            "version": 1,
+            "refresh_interval": "fast",
+            "freeze_on_hover": true,
+            "filter": "fire",
+            "columns": ["name", "from the future", "pid"],
+        });
+        fs::write(dir.join("settings.json"), file.to_string()).unwrap();
+        let settings = SettingsStore::load(&dir).get();
+        assert_eq!(settings.refresh_interval, DEFAULT_REFRESH_INTERVAL);
+        assert!(settings.freeze_on_hover);
+        assert_eq!(settings.filter.as_deref(), Some("fire"));
+        assert!(settings.columns == [Column::Name, Column::Pid]);
+    }
+
+    #[test]
+    fn a_file_that_is_not_json_is_backed_up() {
+        let dir = dir("backup");
+        fs::write(dir.join("settings.json"), "{ not json").unwrap();
+        let store = SettingsStore::load(&dir);
+        assert!(store.get() == Settings::default());
+        store
+            .update(|settings| settings.freeze_on_hover = true)
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("settings.json.bak")).unwrap(),
+            "{ not json"
+        );
+        assert_eq!(stored(&dir)["freeze_on_hover"], json!(true));
+    }
+
+    #[test]
+    fn a_file_that_cannot_be_read_is_left_alone() {
+        let dir = dir("unreadable");
+        // a directory in its place fails to read like a file without permission
+        fs::create_dir(dir.join("settings.json")).unwrap();
+        let store = SettingsStore::load(&dir);
+        assert!(store
+            .update(|settings| settings.freeze_on_hover = true)
+            .is_err());
+        assert!(!store.get().freeze_on_hover);
+        assert!(dir.join("settings.json").is_dir());
+    }
+
+    #[test]
+    fn concurrent_updates_all_land() {
+        let store = SettingsStore::load(&dir("concurrent"));
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        store
+                            .update(|settings| settings.refresh_interval += 10)
+                            .unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(store.get().refresh_interval, DEFAULT_REFRESH_INTERVAL + 800);
+    }
+
+    #[test]
+    fn a_patch_changes_only_its_fields() {
+        let dir = dir("patch");
+        let store = SettingsStore::load(&dir);
+        store
+            .update(|settings| settings.filter = Some("fire".into()))
+            .unwrap();
+        let patch = json!({
+            "freeze_on_hover": true,
+            "refresh_interval": "fast",
+            "exporter": { "enabled": true, "port": 9999 },
+        });
+        let Value::Object(fields) = patch else {
+            unreachable!()
+        };
+        let kept = store.patch(fields).unwrap();
+        assert!(kept.freeze_on_hover);
+        assert_eq!(kept.filter.as_deref(), Some("fire"));
+        assert_eq!(kept.refresh_interval, DEFAULT_REFRESH_INTERVAL);
+        assert!(!kept.exporter.enabled);
+        assert_eq!(stored(&dir)["freeze_on_hover"], json!(true));
+        // null clears an optional field
+        let Value::Object(fields) = json!({ "filter": null }) else {
+            unreachable!()
+        };
+        assert!(store.patch(fields).unwrap().filter.is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Serialize)]
+pub struct ThreadInfo {
+    pub id: u32,
+    pub name: String,
+    pub state: String,
+    pub cpu: f32,
+    pub last_cpu: i32,
+}
+
+struct ThreadSample {
+    taken: Instant,
+    ticks: HashMap<u32, u64>,
+}
+
+// cpu usage of a thread is the tick delta between two calls for the same pid
+#[derive(Default)]
+pub struct ThreadSampler {
+    previous: Mutex<HashMap<u32, ThreadSample>>,
+}
+
+const SAMPLE_TTL: Duration = Duration::from_secs(60);
+
+impl ThreadSampler {
+    pub fn sample(&self, pid: u32) -> Vec<ThreadInfo> {
+        let stats = read_threads(pid);
+        let now = Instant::now();
+        let clock_ticks = clock_ticks();
+
+        let mut previous = self.previous.lock().unwrap();
+        previous.retain(|_, sample| now.duration_since(sample.taken) < SAMPLE_TTL);
+        let last = previous.get(&pid);
+
+        let threads = stats
+            .iter()
+            .map(|stat| {
+                let cpu = last
+                    .and_then(|last| {
+                        let before = *last.ticks.get(&stat.id)?;
+                        let elapsed = now.duration_since(last.taken).as_secs_f32();
+                        (elapsed > 0.0).then(|| {
+                            stat.ticks.saturating_sub(before) as f32 / clock_ticks / elapsed * 100.0
+                        })
+                    })
+                    .unwrap_or(0.0);
+                ThreadInfo {
+                    id: stat.id,
+                    name: stat.name.clone(),
+                    state: state_name(&stat.state).to_string(),
+                    cpu,
+                    last_cpu: stat.last_cpu,
+                }
+            })
+            .collect();
+
+        previous.insert(
+            pid,
+            ThreadSample {
+                taken: now,
+                ticks: stats.iter().map(|stat| (stat.id, stat.ticks)).collect(),
+            },
+        );
+        threads
+    }
+}
+
+struct ThreadStat {
+    id: u32,
+    name: String,
+    state: String,
+    ticks: u64,
+    last_cpu: i32,
+}
+
+fn read_threads(pid: u32) -> Vec<ThreadStat> {
+    let Ok(entries) = fs::read_dir(format!("/proc/{pid}/task")) else {
+        return Vec::new();
+    };
+    let mut threads: Vec<ThreadStat> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_str()?.parse().ok()?;
+            let stat = fs::read_to_string(entry.path().join("stat")).ok()?;
+            parse_stat(id, &stat)
+        })
+        .collect();
+    threads.sort_by_key(|thread| thread.id);
+    threads
+}
+
+// comm may contain spaces and parentheses, so split on the last ')'
+fn parse_stat(id: u32, stat: &str) -> Option<ThreadStat> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let fields: Vec<&str> = stat.get(close + 2..)?.split_whitespace().collect();
+    // fields[0] is field 3 (state) of proc_pid_stat(5)
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(ThreadStat {
+        id,
+        name,
+        state: fields.first()?.to_string(),
+        ticks: utime + stime,
+        last_cpu: fields.get(36)?.parse().ok()?,
+    })
+}
+
+fn state_name(state: &str) -> &str {
+    match state {
+        "R" => "Running",
+        "S" => "Sleeping",
+        "D" => "Disk Sleep",
+        "Z" => "Zombie",
+        "T" => "Stopped",
+        "t" => "Tracing Stop",
+        "X" | "x" => "Dead",
+        "I" => "Idle",
+        other => other,
+    }
+}
+
+fn clock_ticks() -> f32 {
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks > 0 {
+        ticks as f32
+    } else {
+        100.0
+    }
+}
@@ -0,0 +1,487 @@
+use crate::cli::{sort_processes, SortBy};
+use crate::collectors::{self, DiskInfo, ProccessInfo};
+use crate::sampler::SystemSampler;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Block, Borders, Cell, Clear, Gauge, List, ListState, Paragraph, Row, Table, TableState, Tabs,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::time::{Duration, Instant};
+use sysinfo::Disks;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+const SIGNALS: [&str; 6] = ["TERM", "KILL", "HUP", "INT", "STOP", "CONT"];
+const USAGE_BAR_WIDTH: usize = 20;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Processes,
+    FileSystems,
+}
+
+enum Mode {
+    Normal,
+    Filter,
+    // picking one of SIGNALS for the process selected when s was pressed
+    Signal {
+        list: ListState,
+        pid: usize,
+        name: String,
+    },
+}
+
+struct App {
+    sampler: SystemSampler,
+    disks: Disks,
+    tab: Tab,
+    mode: Mode,
+    processes: Vec<ProccessInfo>,
+    file_systems: Vec<DiskInfo>,
+    cpu: f32,
+    memory: (u64, u64),
+    swap: (u64, u64),
+    sort: SortBy,
+    reverse: bool,
+    filter: String,
+    table: TableState,
+    status: String,
+}
+
+// the processes table, file systems and gauges in the terminal, until q is pressed
+pub fn run() -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new().run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new() -> Self {
+        let mut app = App {
+            sampler: SystemSampler::default(),
+            disks: Disks::new_with_refreshed_list(),
+            tab: Tab::Processes,
+            mode: Mode::Normal,
+            processes: Vec::new(),
+            file_systems: Vec::new(),
+            cpu: 0.0,
+            memory: (0, 0),
+            swap: (0, 0),
+            sort: SortBy::Cpu,
+            reverse: false,
+            filter: String::new(),
+            table: TableState::default().with_selected(Some(0)),
+            status: String::new(),
+        };
+        app.refresh();
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let mut refreshed = Instant::now();
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let timeout = REFRESH_INTERVAL.saturating_sub(refreshed.elapsed());
+            if event::poll(timeout)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.on_key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+            if refreshed.elapsed() >= REFRESH_INTERVAL {
+                self.refresh();
+                refreshed = Instant::now();
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        let selected = self.selected_process().map(|(pid, _)| pid);
+        let (processes, cpu, memory, swap) = self.sampler.with_system(|sys| {
+            (
+                collectors::process_infos(sys, None),
+                sys.global_cpu_usage(),
+                (sys.used_memory(), sys.total_memory()),
+                (sys.used_swap(), sys.total_swap()),
+            )
+        });
+        self.processes = processes;
+        self.resort();
+        self.follow(selected);
+        self.cpu = cpu;
+        self.memory = memory;
+        self.swap = swap;
+        self.disks.refresh(true);
+        self.file_systems = collectors::disk_infos(&self.disks, true);
+    }
+
+    fn resort(&mut self) {
+        sort_processes(&mut self.processes, self.sort);
+        if self.reverse {
+            self.processes.reverse();
+        }
+    }
+
+    // keeps the selection on the same process after the rows moved
+    fn follow(&mut self, pid: Option<usize>) {
+        let Some(pid) = pid else {
+            return;
+        };
+        let pid = pid.to_string();
+        if let Some(index) = self.visible().iter().position(|process| process.id == pid) {
+            self.table.select(Some(index));
+        }
+    }
+
+    // processes as shown in the table, already sorted
+    fn visible(&self) -> Vec<&ProccessInfo> {
+        let filter = self.filter.to_lowercase();
+        self.processes
+            .iter()
+            .filter(|process| process.name.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn rows(&self) -> usize {
+        match self.tab {
+            Tab::Processes => self.visible().len(),
+            Tab::FileSystems => self.file_systems.len(),
+        }
+    }
+
+    fn select(&mut self, offset: isize) {
+        let rows = self.rows();
+        if rows == 0 {
+            self.table.select(None);
+            return;
+        }
+        let current = self.table.selected().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, rows as isize - 1);
+        self.table.select(Some(next as usize));
+    }
+
+    fn selected_process(&self) -> Option<(usize, String)> {
+        let visible = self.visible();
+        let process = visible.get(self.table.selected()?)?;
+        Some((process.id.parse().ok()?, process.name.clone()))
+    }
+
+    // false once the app should quit
+    fn on_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        match &mut self.mode {
+            Mode::Filter => match key.code {
+                KeyCode::Enter => self.mode = Mode::Normal,
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.mode = Mode::Normal;
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.table.select(Some(0));
+                }
+                _ => {}
+            },
+            Mode::Signal { list, pid, name } => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => list.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => list.select_next(),
+                KeyCode::Enter => {
+                    let signal = SIGNALS[list.selected().unwrap_or(0).min(SIGNALS.len() - 1)];
+                    let (pid, name) = (*pid, std::mem::take(name));
+                    self.mode = Mode::Normal;
+                    self.send_signal(pid, &name, signal);
+                }
+                KeyCode::Esc => self.mode = Mode::Normal,
+                _ => {}
+            },
+            Mode::Normal => match key.code {
+                KeyCode::Char('q') => return false,
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.tab = match self.tab {
+                        Tab::Processes => Tab::FileSystems,
+                        Tab::FileSystems => Tab::Processes,
+                    };
+                    self.table.select(Some(0));
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::PageDown => self.select(20),
+                KeyCode::PageUp => self.select(-20),
+                KeyCode::Home | KeyCode::Char('g') => self.table.select(Some(0)),
+                KeyCode::End | KeyCode::Char('G') => self.select(isize::MAX / 2),
+                KeyCode::Char(c @ ('c' | 'm' | 'n')) if self.tab == Tab::Processes => {
+                    let sort = match c {
+                        'c' => SortBy::Cpu,
+                        'm' => SortBy::Memory,
+                        _ => SortBy::Name,
+                    };
+                    // picking the current column again flips the order
+                    self.reverse = sort == self.sort && !self.reverse;
+                    self.sort = sort;
+                    let selected = self.selected_process().map(|(pid, _)| pid);
+                    self.resort();
+                    self.follow(selected);
+                }
+                KeyCode::Char('/') if self.tab == Tab::Processes => self.mode = Mode::Filter,
+                KeyCode::Char('s')
+                    if self.tab == Tab::Processes && self.selected_process().is_some() =>
+                {
+                    if let Some((pid, name)) = self.selected_process() {
+                        self.mode = Mode::Signal {
+                            list: ListState::default().with_selected(Some(0)),
+                            pid,
+                            name,
+                        };
+                    }
+                }
+                _ => {}
+            },
+        }
+        true
+    }
+
+    fn send_signal(&mut self, pid: usize, name: &str, signal: &str) {
+        self.status = match collectors::signal_process(pid, signal) {
+            Some(true) => format!("sent {} to {} ({})", signal, name, pid),
+            _ => format!("could not send {} to {} ({})", signal, name, pid),
+        };
+        self.refresh();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs, gauges, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let titles = ["Processes", "File Systems"];
+        let selected = match self.tab {
+            Tab::Processes => 0,
+            Tab::FileSystems => 1,
+        };
+        frame.render_widget(
+            Tabs::new(titles).select(selected).highlight_style(
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            tabs,
+        );
+
+        self.draw_gauges(frame, gauges);
+        match self.tab {
+            Tab::Processes => self.draw_processes(frame, body),
+            Tab::FileSystems => self.draw_file_systems(frame, body),
+        }
+
+        let help = match self.mode {
+            Mode::Filter => format!("filter: {}_  (enter keep, esc clear)", self.filter),
+            _ if !self.status.is_empty() => self.status.clone(),
+            _ => "q quit  tab switch  j/k move  c/m/n sort  / filter  s signal".to_string(),
+        };
+        frame.render_widget(Paragraph::new(help), footer);
+
+        if let Mode::Signal { list, .. } = &mut self.mode {
+            let area = centered(frame.area(), 24, SIGNALS.len() as u16 + 2);
+            frame.render_widget(Clear, area);
+            frame.render_stateful_widget(
+                List::new(SIGNALS)
+                    .block(Block::default().borders(Borders::ALL).title("Send signal"))
+                    .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+                area,
+                list,
+            );
+        }
+    }
+
+    fn draw_gauges(&self, frame: &mut Frame, area: Rect) {
+        let [cpu, memory, swap] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(area);
+        let ratio = |(used, total): (u64, u64)| {
+            if total > 0 {
+                used as f64 / total as f64
+            } else {
+                0.0
+            }
+        };
+        let gauge = |title: &'static str, ratio: f64, label: String| {
+            Gauge::default()
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .gauge_style(Style::default().fg(usage_color(ratio * 100.0)))
+                .ratio(ratio.clamp(0.0, 1.0))
+                .label(label)
+        };
+        frame.render_widget(
+            gauge("CPU", self.cpu as f64 / 100.0, format!("{:.1}%", self.cpu)),
+            cpu,
+        );
+        frame.render_widget(
+            gauge(
+                "Memory",
+                ratio(self.memory),
+                format!(
+                    "{} / {}",
+                    collectors::format_memory_size(self.memory.0),
+                    collectors::format_memory_size(self.memory.1)
+                ),
+            ),
+            memory,
+        );
+        frame.render_widget(
+            gauge(
+                "Swap",
+                ratio(self.swap),
+                format!(
+                    "{} / {}",
+                    collectors::format_memory_size(self.swap.0),
+                    collectors::format_memory_size(self.swap.1)
+                ),
+            ),
+            swap,
+        );
+    }
+
+    fn draw_processes(&mut self, frame: &mut Frame, area: Rect) {
+        let arrow = if self.reverse { "▲" } else { "▼" };
+        let title = |label: &str, sort: SortBy| {
+            if sort == self.sort {
+                format!("{} {}", label, arrow)
+            } else {
+                label.to_string()
+            }
+        };
+        let header = Row::new([
+            "PID".to_string(),
+            title("NAME", SortBy::Name),
+            title("CPU %", SortBy::Cpu),
+            title("MEMORY MB", SortBy::Memory),
+            "READ MB".to_string(),
+            "WRITE MB".to_string(),
+            "THREADS".to_string(),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows: Vec<Row> = self
+            .visible()
+            .into_iter()
+            .map(|process| {
+                Row::new([
+                    Cell::from(process.id.clone()),
+                    Cell::from(process.name.clone()),
+                    Cell::from(format!("{:.1}", process.cpu)),
+                    Cell::from(process.memory.to_string()),
+                    Cell::from(process.disk_read.to_string()),
+                    Cell::from(process.disk_write.to_string()),
+                    Cell::from(process.threads.to_string()),
+                ])
+            })
+            .collect();
+        let count = rows.len();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(8),
+                Constraint::Min(20),
+                Constraint::Length(8),
+                Constraint::Length(12),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Processes ({})", count)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_file_systems(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new([
+            "DEVICE",
+            "TYPE",
+            "SIZE",
+            "USED",
+            "AVAIL",
+            "USE",
+            "MOUNTED ON",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+        let rows: Vec<Row> = self
+            .file_systems
+            .iter()
+            .map(|disk| {
+                let filled = (disk.percent_used / 100.0 * USAGE_BAR_WIDTH as f64).round() as usize;
+                let bar = format!(
+                    "{}{} {:.0}%",
+                    "█".repeat(filled.min(USAGE_BAR_WIDTH)),
+                    "░".repeat(USAGE_BAR_WIDTH - filled.min(USAGE_BAR_WIDTH)),
+                    disk.percent_used
+                );
+                Row::new([
+                    Cell::from(disk.device.clone()),
+                    Cell::from(disk.r#type.clone()),
+                    Cell::from(disk.total.clone()),
+                    Cell::from(disk.used.clone()),
+                    Cell::from(disk.available.clone()),
+                    Cell::from(
+                        Line::from(bar).style(Style::default().fg(usage_color(disk.percent_used))),
+                    ),
+                    Cell::from(disk.directory.clone()),
+                ])
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(12),
+                Constraint::Length(8),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Length(USAGE_BAR_WIDTH as u16 + 5),
+                Constraint::Min(12),
+            ],
+        )
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("File Systems"))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+}
+
+// same thresholds as the file systems page
+fn usage_color(percent: f64) -> Color {
+    if percent >= 90.0 {
+        Color::Red
+    } else if percent >= 80.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
@@ -0,0 +1,478 @@
+use super::export::TimeRange;
+use super::hosts::is_remote;
+use super::time_travel::format_timestamp;
+use super::{invoke, try_invoke};
+use crate::components::navbar::NavBar;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+// how far back the log goes
+const HISTORY_MS: u64 = 24 * 60 * 60 * 1000;
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+const AUDIT_ENTRIES: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Condition {
+    ProcessCpu { name: String, above: f64 },
+    MountUsage { mount_point: String, above: f64 },
+    SwapUsed { above: u64 },
+    ProcessMissing { name: String },
+}
+
+impl Condition {
+    fn describe(&self) -> String {
+        match self {
+            Condition::ProcessCpu { name, above } => format!("{} cpu > {}%", name, above),
+            Condition::MountUsage { mount_point, above } => {
+                format!("{} used > {}%", mount_point, above)
+            }
+            Condition::SwapUsed { above } => {
+                format!("swap used > {:.1} GB", *above as f64 / GB)
+            }
+            Condition::ProcessMissing { name } => format!("{} not running", name),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Action {
+    Renice { nice: i32 },
+    Signal { signal: String },
+    Kill,
+    Shell { command: String },
+}
+
+impl Action {
+    fn describe(&self) -> String {
+        match self {
+            Action::Renice { nice } => format!("renice to {}", nice),
+            Action::Signal { signal } => format!("send {}", signal),
+            Action::Kill => "kill".to_string(),
+            Action::Shell { command } => format!("run {}", command),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct Rule {
+    id: String,
+    name: String,
+    enabled: bool,
+    condition: Condition,
+    for_secs: u64,
+    action: Option<Action>,
+    dry_run: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct FiringAlert {
+    rule_id: String,
+    rule_name: String,
+    since: u64,
+    message: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct AlertEvent {
+    timestamp: u64,
+    rule_name: String,
+    kind: String,
+    message: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct AuditEntry {
+    timestamp: u64,
+    rule_name: String,
+    action: String,
+    pid: Option<u32>,
+    dry_run: bool,
+    outcome: String,
+}
+
+#[derive(Serialize)]
+struct AuditArgs {
+    limit: usize,
+}
+
+#[derive(Serialize)]
+struct RulesArgs {
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+struct HistoryArgs {
+    range: TimeRange,
+}
+
+fn now() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+// alert rules for this machine, what is firing right now and what fired lately
+#[function_component(Alerts)]
+pub fn alerts() -> Html {
+    let rules = use_state_eq(Vec::<Rule>::new);
+    let firing = use_state_eq(Vec::<FiringAlert>::new);
+    let log = use_state_eq(Vec::<AlertEvent>::new);
+    let audit = use_state_eq(Vec::<AuditEntry>::new);
+    let error = use_state(|| None::<String>);
+    let kind = use_state(|| "process_cpu".to_string());
+    let name_input = use_node_ref();
+    let target_input = use_node_ref();
+    let threshold_input = use_node_ref();
+    let for_input = use_node_ref();
+    let action_kind = use_state(|| "none".to_string());
+    let action_input = use_node_ref();
+    let dry_run_input = use_node_ref();
+
+    let get_rules = use_async({
+        let rules = rules.clone();
+        async move {
+            let result = invoke("alert_rules", JsValue::NULL).await;
+            if let Ok(current) = from_value::<Vec<Rule>>(result) {
+                rules.set(current);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    let get_alerts = use_async({
+        let firing = firing.clone();
+        let log = log.clone();
+        let audit = audit.clone();
+        async move {
+            let result = invoke("firing_alerts", JsValue::NULL).await;
+            if let Ok(current) = from_value::<Vec<FiringAlert>>(result) {
+                firing.set(current);
+            }
+            let range = TimeRange {
+                start: now().saturating_sub(HISTORY_MS),
+                end: now(),
+            };
+            let result = invoke("alert_history", to_value(&HistoryArgs { range }).unwrap()).await;
+            if let Ok(mut events) = from_value::<Vec<AlertEvent>>(result) {
+                events.reverse();
+                log.set(events);
+            }
+            let args = to_value(&AuditArgs {
+                limit: AUDIT_ENTRIES,
+            })
+            .unwrap();
+            if let Ok(entries) = from_value::<Vec<AuditEntry>>(invoke("audit_log", args).await) {
+                audit.set(entries);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((), {
+        let get_rules = get_rules.clone();
+        let get_alerts = get_alerts.clone();
+        move |_| {
+            get_rules.run();
+            get_alerts.run();
+        }
+    });
+
+    {
+        let get_alerts = get_alerts.clone();
+        use_interval(
+            move || {
+                get_alerts.run();
+            },
+            5000,
+        )
+    }
+
+    let save = {
+        let get_rules = get_rules.clone();
+        let error = error.clone();
+        Callback::from(move |next: Vec<Rule>| {
+            let get_rules = get_rules.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let args = to_value(&RulesArgs { rules: next }).unwrap();
+                match try_invoke("set_alert_rules", args).await {
+                    Ok(_) => error.set(None),
+                    Err(err) => error.set(from_value::<String>(err).ok()),
+                }
+                get_rules.run();
+            });
+        })
+    };
+
+    let on_kind = {
+        let kind = kind.clone();
+        Callback::from(move |e: Event| {
+            kind.set(e.target_unchecked_into::<HtmlSelectElement>().value());
+        })
+    };
+
+    let on_action_kind = {
+        let action_kind = action_kind.clone();
+        Callback::from(move |e: Event| {
+            action_kind.set(e.target_unchecked_into::<HtmlSelectElement>().value());
+        })
+    };
+
+    let on_add = {
+        let rules = rules.clone();
+        let save = save.clone();
+        let error = error.clone();
+        let kind = kind.clone();
+        let name_input = name_input.clone();
+        let target_input = target_input.clone();
+        let threshold_input = threshold_input.clone();
+        let for_input = for_input.clone();
+        let action_kind = action_kind.clone();
+        let action_input = action_input.clone();
+        let dry_run_input = dry_run_input.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let value = |input: &NodeRef| {
+                input
+                    .cast::<HtmlInputElement>()
+                    .map(|input| input.value().trim().to_string())
+                    .unwrap_or_default()
+            };
+            let target = value(&target_input);
+            let threshold = value(&threshold_input).parse::<f64>();
+            let condition = match (kind.as_str(), threshold) {
+                ("process_missing", _) => Condition::ProcessMissing { name: target },
+                (_, Err(_)) => {
+                    error.set(Some("the threshold has to be a number".into()));
+                    return;
+                }
+                ("process_cpu", Ok(above)) => Condition::ProcessCpu {
+                    name: target,
+                    above,
+                },
+                ("mount_usage", Ok(above)) => Condition::MountUsage {
+                    mount_point: target,
+                    above,
+                },
+                (_, Ok(above)) => Condition::SwapUsed {
+                    above: (above * GB) as u64,
+                },
+            };
+            let parameter = value(&action_input);
+            let action = match action_kind.as_str() {
+                "renice" => match parameter.parse() {
+                    Ok(nice) => Some(Action::Renice { nice }),
+                    Err(_) => {
+                        error.set(Some("the nice value has to be a number".into()));
+                        return;
+                    }
+                },
+                "signal" => Some(Action::Signal { signal: parameter }),
+                "kill" => Some(Action::Kill),
+                "shell" => Some(Action::Shell { command: parameter }),
+                _ => None,
+            };
+            let dry_run = dry_run_input
+                .cast::<HtmlInputElement>()
+                .is_some_and(|input| input.checked());
+            let name = value(&name_input);
+            let mut next = (*rules).clone();
+            next.push(Rule {
+                id: String::new(),
+                name: if name.is_empty() {
+                    condition.describe()
+                } else {
+                    name
+                },
+                enabled: true,
+                condition,
+                for_secs: value(&for_input).parse().unwrap_or(0),
+                action,
+                dry_run,
+            });
+            save.emit(next);
+        })
+    };
+
+    let input_class = "p-2 text-sm bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600 dark:text-white";
+    let cell_class =
+        "px-4 py-2 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300";
+    let heading_class = "px-4 pt-4 text-base font-semibold text-gray-800 dark:text-gray-200";
+    let (target_placeholder, threshold_placeholder) = match kind.as_str() {
+        "process_cpu" => ("Process name", Some("CPU %")),
+        "mount_usage" => ("Mount point", Some("Used %")),
+        "swap_used" => ("", Some("GB")),
+        _ => ("Process name", None),
+    };
+    let action_placeholder = match action_kind.as_str() {
+        "renice" => Some("Nice, -20 to 19"),
+        "signal" => Some("TERM, HUP, ..."),
+        "shell" => Some("Command, $PROCCESSMANAGER_PID is the process"),
+        _ => None,
+    };
+
+    html! {
+    <>
+        <NavBar />
+        if is_remote() {
+            <div class="px-4 pt-4 text-sm text-gray-500 dark:text-gray-400">{"Alert rules watch this machine, not the selected remote host."}</div>
+        }
+        <div class={heading_class}>{"Firing"}</div>
+        <div class="px-4 py-2">
+            if firing.is_empty() {
+                <span class="text-sm text-gray-500 dark:text-gray-400">{"Nothing is firing."}</span>
+            }
+            {for firing.iter().map(|alert| html! {
+                <div key={alert.rule_id.clone()} class="p-2 mb-2 text-sm text-red-800 bg-red-50 border border-red-300 rounded-lg dark:bg-gray-800 dark:text-red-400 dark:border-red-800">
+                    <span class="font-semibold">{alert.rule_name.clone()}</span>
+                    {format!(" {} since {}", alert.message, format_timestamp(alert.since))}
+                </div>
+            })}
+        </div>
+
+        <div class={heading_class}>{"Rules"}</div>
+        <form onsubmit={on_add} class="px-4 py-2 flex flex-wrap items-center gap-2">
+            <input ref={name_input} placeholder="Name" class={input_class} />
+            <select onchange={on_kind} class={input_class}>
+                <option value="process_cpu" selected={*kind == "process_cpu"}>{"Process cpu above"}</option>
+                <option value="mount_usage" selected={*kind == "mount_usage"}>{"Mount usage above"}</option>
+                <option value="swap_used" selected={*kind == "swap_used"}>{"Swap used above"}</option>
+                <option value="process_missing" selected={*kind == "process_missing"}>{"Process not running"}</option>
+            </select>
+            if !target_placeholder.is_empty() {
+                <input ref={target_input} required=true placeholder={target_placeholder} class={input_class} />
+            }
+            if let Some(placeholder) = threshold_placeholder {
+                <input ref={threshold_input} required=true type="number" step="any" min="0" placeholder={placeholder} class={classes!(input_class, "w-24")} />
+            }
+            <label class="text-sm text-gray-800 dark:text-gray-300">
+                {"for "}
+                <input ref={for_input} type="number" min="0" value="30" class={classes!(input_class, "w-20")} />
+                {" s"}
+            </label>
+            <select onchange={on_action_kind} class={input_class}>
+                <option value="none" selected={*action_kind == "none"}>{"Only notify"}</option>
+                <option value="renice" selected={*action_kind == "renice"}>{"Renice"}</option>
+                <option value="signal" selected={*action_kind == "signal"}>{"Send signal"}</option>
+                <option value="kill" selected={*action_kind == "kill"}>{"Kill"}</option>
+                <option value="shell" selected={*action_kind == "shell"}>{"Run command"}</option>
+            </select>
+            if let Some(placeholder) = action_placeholder {
+                <input ref={action_input} required=true placeholder={placeholder} class={input_class} />
+            }
+            if *action_kind != "none" {
+                <label class="inline-flex items-center text-sm text-gray-800 dark:text-gray-300">
+                    <input ref={dry_run_input} type="checkbox" checked=true class="w-4 h-4 me-2" />
+                    {"Dry run"}
+                </label>
+            }
+            <button type="submit" class="text-white bg-blue-700 hover:bg-blue-800 rounded-lg text-sm px-4 py-2 dark:bg-blue-600 dark:hover:bg-blue-700">{"Add rule"}</button>
+            if let Some(message) = &*error {
+                <span class="text-sm text-red-600 dark:text-red-500">{message.clone()}</span>
+            }
+        </form>
+        <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+            <thead class="bg-gray-100 dark:bg-gray-700">
+                <tr>
+                    <th class="p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">{"Enabled"}</th>
+                    <th class="p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">{"Name"}</th>
+                    <th class="p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">{"Condition"}</th>
+                    <th class="p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">{"For"}</th>
+                    <th class="p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">{"Action"}</th>
+                    <th class="p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">{"Dry run"}</th>
+                    <th class="p-4"></th>
+                </tr>
+            </thead>
+            <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                {for rules.iter().enumerate().map(|(index, rule)| {
+                    let on_toggle = {
+                        let rules = rules.clone();
+                        let save = save.clone();
+                        Callback::from(move |_| {
+                            let mut next = (*rules).clone();
+                            next[index].enabled = !next[index].enabled;
+                            save.emit(next);
+                        })
+                    };
+                    let on_dry_run = {
+                        let rules = rules.clone();
+                        let save = save.clone();
+                        Callback::from(move |_| {
+                            let mut next = (*rules).clone();
+                            next[index].dry_run = !next[index].dry_run;
+                            save.emit(next);
+                        })
+                    };
+                    let on_delete = {
+                        let rules = rules.clone();
+                        let save = save.clone();
+                        Callback::from(move |_| {
+                            let mut next = (*rules).clone();
+                            next.remove(index);
+                            save.emit(next);
+                        })
+                    };
+                    html! {
+                        <tr key={rule.id.clone()} class="hover:bg-gray-100 dark:hover:bg-gray-700">
+                            <td class={cell_class}><input type="checkbox" checked={rule.enabled} onchange={on_toggle} class="w-4 h-4" /></td>
+                            <td class={cell_class}>{rule.name.clone()}</td>
+                            <td class={cell_class}>{rule.condition.describe()}</td>
+                            <td class={cell_class}>{format!("{} s", rule.for_secs)}</td>
+                            <td class={cell_class}>{rule.action.as_ref().map_or("-".to_string(), Action::describe)}</td>
+                            <td class={cell_class}>
+                                if rule.action.is_some() {
+                                    <input type="checkbox" checked={rule.dry_run} onchange={on_dry_run} class="w-4 h-4" />
+                                }
+                            </td>
+                            <td class={cell_class}>
+                                <button onclick={on_delete} class="text-gray-600 hover:text-red-600 dark:text-gray-300">{"Delete"}</button>
+                            </td>
+                        </tr>
+                    }
+                })}
+            </tbody>
+        </table>
+
+        <div class={heading_class}>{"Last 24 hours"}</div>
+        <div class="px-4 py-2 text-sm text-gray-800 dark:text-gray-300">
+            if log.is_empty() {
+                <span class="text-gray-500 dark:text-gray-400">{"No alerts."}</span>
+            }
+            {for log.iter().map(|event| {
+                let class = if event.kind == "fired" { "text-red-600 dark:text-red-500" } else { "text-green-600 dark:text-green-500" };
+                html! {
+                    <div class="py-1">
+                        <span class="text-gray-500 dark:text-gray-400">{format_timestamp(event.timestamp)}</span>
+                        <span class={classes!(class, "mx-2", "font-semibold")}>{event.kind.clone()}</span>
+                        <span class="font-semibold">{event.rule_name.clone()}</span>
+                        {format!(" {}", event.message)}
+                    </div>
+                }
+            })}
+        </div>
+
+        <div class={heading_class}>{"Automatic actions"}</div>
+        <div class="px-4 py-2 text-sm text-gray-800 dark:text-gray-300">
+            if audit.is_empty() {
+                <span class="text-gray-500 dark:text-gray-400">{"No actions taken."}</span>
+            }
+            {for audit.iter().map(|entry| html! {
+                <div class="py-1">
+                    <span class="text-gray-500 dark:text-gray-400">{format_timestamp(entry.timestamp)}</span>
+                    <span class="mx-2 font-semibold">{entry.rule_name.clone()}</span>
+                    {entry.action.clone()}
+                    {entry.pid.map(|pid| format!(" on {}", pid)).unwrap_or_default()}
+                    <span class="mx-2 text-gray-500 dark:text-gray-400">
+                        {if entry.dry_run { format!("{} (dry run)", entry.outcome) } else { entry.outcome.clone() }}
+                    </span>
+                </div>
+            })}
+        </div>
+    </>
+    }
+}
@@ -0,0 +1,303 @@
+use super::format_size;
+use super::processes::ProccessInfo;
+use super::time_travel::format_timestamp;
+use crate::components::sparkline::Sparkline;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use yew::prelude::*;
+
+// the same names the backend uses, it only works out the fields of the shown columns
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum Column {
+    Name,
+    Pid,
+    Cpu,
+    Memory,
+    DiskRead,
+    DiskWrite,
+    Threads,
+    Ppid,
+    User,
+    Status,
+    Nice,
+    StartTime,
+    Runtime,
+    CommandLine,
+    Exe,
+    CpuTime,
+    VirtualMemory,
+    ReadRate,
+    WriteRate,
+    OpenFiles,
+    Cgroup,
+}
+
+pub(super) const ALL: [Column; 21] = [
+    Column::Name,
+    Column::Pid,
+    Column::Cpu,
+    Column::Memory,
+    Column::DiskRead,
+    Column::DiskWrite,
+    Column::Threads,
+    Column::Ppid,
+    Column::User,
+    Column::Status,
+    Column::Nice,
+    Column::StartTime,
+    Column::Runtime,
+    Column::CommandLine,
+    Column::Exe,
+    Column::CpuTime,
+    Column::VirtualMemory,
+    Column::ReadRate,
+    Column::WriteRate,
+    Column::OpenFiles,
+    Column::Cgroup,
+];
+
+pub(super) const DEFAULT_COLUMNS: [Column; 7] = [
+    Column::Name,
+    Column::Pid,
+    Column::Cpu,
+    Column::Memory,
+    Column::DiskRead,
+    Column::DiskWrite,
+    Column::Threads,
+];
+
+// inline next to the numbers, cpu is scaled to at least one full core
+fn trend(values: Vec<f64>, max: Option<f64>) -> Html {
+    if values.len() < 2 {
+        return html! {};
+    }
+    html! {
+        <span class="inline-block ms-2 align-middle">
+            <Sparkline {values} {max} width={60} height={16} />
+        </span>
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    let (days, rest) = (seconds / 86400, seconds % 86400);
+    let clock = format!(
+        "{:02}:{:02}:{:02}",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    );
+    if days > 0 {
+        format!("{}d {}", days, clock)
+    } else {
+        clock
+    }
+}
+
+fn or_dash<T>(value: &Option<T>, format: impl Fn(&T) -> String) -> Html {
+    html! { <>{value.as_ref().map_or("-".to_string(), format)}</> }
+}
+
+impl Column {
+    pub(super) fn label(&self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Pid => "ID",
+            Column::Cpu => "CPU",
+            Column::Memory => "Memory",
+            Column::DiskRead => "Disk Read",
+            Column::DiskWrite => "Disk Write",
+            Column::Threads => "Threads",
+            Column::Ppid => "Parent",
+            Column::User => "User",
+            Column::Status => "Status",
+            Column::Nice => "Nice",
+            Column::StartTime => "Started",
+            Column::Runtime => "Runtime",
+            Column::CommandLine => "Command Line",
+            Column::Exe => "Executable",
+            Column::CpuTime => "CPU Time",
+            Column::VirtualMemory => "Virtual Memory",
+            Column::ReadRate => "Read Rate",
+            Column::WriteRate => "Write Rate",
+            Column::OpenFiles => "Open Files",
+            Column::Cgroup => "Cgroup",
+        }
+    }
+
+    // missing values come first when ascending
+    pub(super) fn compare(&self, a: &ProccessInfo, b: &ProccessInfo) -> Ordering {
+        match self {
+            Column::Name => a.name.cmp(&b.name),
+            Column::Pid => a.id.parse::<u32>().ok().cmp(&b.id.parse::<u32>().ok()),
+            Column::Cpu => a.cpu.total_cmp(&b.cpu),
+            Column::Memory => a.memory.cmp(&b.memory),
+            Column::DiskRead => a.disk_read.cmp(&b.disk_read),
+            Column::DiskWrite => a.disk_write.cmp(&b.disk_write),
+            Column::Threads => a.threads.cmp(&b.threads),
+            Column::Ppid => a.ppid.cmp(&b.ppid),
+            Column::User => a.user.cmp(&b.user),
+            Column::Status => a.status.cmp(&b.status),
+            Column::Nice => a.nice.cmp(&b.nice),
+            Column::StartTime => a.start_time.cmp(&b.start_time),
+            Column::Runtime => a.run_time.cmp(&b.run_time),
+            Column::CommandLine => a.command_line.cmp(&b.command_line),
+            Column::Exe => a.exe.cmp(&b.exe),
+            Column::CpuTime => a.cpu_time.cmp(&b.cpu_time),
+            Column::VirtualMemory => a.virtual_memory.cmp(&b.virtual_memory),
+            Column::ReadRate => a.read_rate.cmp(&b.read_rate),
+            Column::WriteRate => a.write_rate.cmp(&b.write_rate),
+            Column::OpenFiles => a.open_files.cmp(&b.open_files),
+            Column::Cgroup => a.cgroup.cmp(&b.cgroup),
+        }
+    }
+
+    // recorded history only has the name, pid, cpu and memory
+    pub(super) fn cell(&self, process: &ProccessInfo, live: bool) -> Html {
+        if !live
+            && !matches!(
+                self,
+                Column::Name | Column::Pid | Column::Cpu | Column::Memory
+            )
+        {
+            return html! { <>{"-"}</> };
+        }
+        match self {
+            Column::Name => html! { <>{process.name.clone()}</> },
+            Column::Pid => html! { <>{process.id.clone()}</> },
+            Column::Cpu => html! {
+                <>
+                    {process.cpu}{" %"}
+                    {trend(
+                        process.cpu_trend.iter().map(|cpu| *cpu as f64).collect(),
+                        Some(process.cpu_trend.iter().fold(100.0, |max, cpu| max.max(*cpu as f64))),
+                    )}
+                </>
+            },
+            Column::Memory => html! {
+                <>
+                    {process.memory}{" MB"}
+                    {trend(process.memory_trend.iter().map(|memory| *memory as f64).collect(), None)}
+                </>
+            },
+            Column::DiskRead => html! { <>{process.disk_read}{" MB"}</> },
+            Column::DiskWrite => html! { <>{process.disk_write}{" MB"}</> },
+            Column::Threads => html! { <>{process.threads}</> },
+            Column::Ppid => or_dash(&process.ppid, |pid| pid.to_string()),
+            Column::User => or_dash(&process.user, String::clone),
+            Column::Status => or_dash(&process.status, String::clone),
+            Column::Nice => or_dash(&process.nice, |nice| nice.to_string()),
+            Column::StartTime => html! { <>{format_timestamp(process.start_time * 1000)}</> },
+            Column::Runtime => or_dash(&process.run_time, |seconds| format_duration(*seconds)),
+            Column::CommandLine => or_dash(&process.command_line, String::clone),
+            Column::Exe => or_dash(&process.exe, String::clone),
+            Column::CpuTime => or_dash(&process.cpu_time, |millis| format_duration(millis / 1000)),
+            Column::VirtualMemory => or_dash(&process.virtual_memory, |bytes| format_size(*bytes)),
+            Column::ReadRate => or_dash(&process.read_rate, |rate| {
+                format!("{}/s", format_size(*rate))
+            }),
+            Column::WriteRate => or_dash(&process.write_rate, |rate| {
+                format!("{}/s", format_size(*rate))
+            }),
+            Column::OpenFiles => or_dash(&process.open_files, |count| count.to_string()),
+            Column::Cgroup => or_dash(&process.cgroup, String::clone),
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub(super) struct ColumnChooserProps {
+    pub columns: Vec<Column>,
+    pub on_change: Callback<Vec<Column>>,
+}
+
+// shows and hides columns and moves the shown ones, the name always stays
+#[function_component(ColumnChooser)]
+pub(super) fn column_chooser(props: &ColumnChooserProps) -> Html {
+    let open = use_state(|| false);
+
+    let on_toggle_open = {
+        let open = open.clone();
+        Callback::from(move |_: MouseEvent| open.set(!*open))
+    };
+
+    let button_class = "text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-sm px-4 py-2.5 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700";
+    let arrow_class = "px-1 text-gray-500 hover:text-gray-900 disabled:opacity-30 dark:text-gray-400 dark:hover:text-white";
+
+    // the shown columns in their order, then the hidden ones
+    let hidden = ALL
+        .iter()
+        .filter(|column| !props.columns.contains(column))
+        .copied();
+    let entries: Vec<(Column, Option<usize>)> = props
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(index, column)| (*column, Some(index)))
+        .chain(hidden.map(|column| (column, None)))
+        .collect();
+
+    html! {
+        <div class="relative">
+            <button type="button" onclick={on_toggle_open} class={button_class}>{"Columns"}</button>
+            if *open {
+                <div class="absolute right-0 z-20 mt-2 w-64 max-h-96 overflow-y-auto p-2 bg-white border border-gray-200 rounded-lg shadow text-sm text-gray-800 dark:bg-gray-700 dark:border-gray-600 dark:text-gray-200">
+                    {for entries.into_iter().map(|(column, index)| {
+                        let columns = props.columns.clone();
+                        let on_change = props.on_change.clone();
+                        let on_check = {
+                            let columns = columns.clone();
+                            let on_change = on_change.clone();
+                            Callback::from(move |_: Event| {
+                                let mut next = columns.clone();
+                                match index {
+                                    Some(index) => {
+                                        next.remove(index);
+                                    }
+                                    None => next.push(column),
+                                }
+                                on_change.emit(next);
+                            })
+                        };
+                        let move_by = |offset: isize| {
+                            let columns = columns.clone();
+                            let on_change = on_change.clone();
+                            Callback::from(move |_: MouseEvent| {
+                                let Some(index) = index else {
+                                    return;
+                                };
+                                let target = index as isize + offset;
+                                if target < 0 || target as usize >= columns.len() {
+                                    return;
+                                }
+                                let mut next = columns.clone();
+                                next.swap(index, target as usize);
+                                on_change.emit(next);
+                            })
+                        };
+                        html! {
+                            <div class="flex items-center justify-between py-1">
+                                <label class="inline-flex items-center">
+                                    <input
+                                        type="checkbox"
+                                        checked={index.is_some()}
+                                        disabled={column == Column::Name}
+                                        onchange={on_check}
+                                        class="w-4 h-4 me-2"
+                                    />
+                                    {column.label()}
+                                </label>
+                                if let Some(index) = index {
+                                    <span>
+                                        <button type="button" disabled={index == 0} onclick={move_by(-1)} class={arrow_class}>{"▲"}</button>
+                                        <button type="button" disabled={index + 1 == props.columns.len()} onclick={move_by(1)} class={arrow_class}>{"▼"}</button>
+                                    </span>
+                                }
+                            </div>
+                        }
+                    })}
+                </div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,130 @@
+use super::{format_size, invoke};
+use crate::components::sparkline::Sparkline;
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use std::collections::{HashMap, VecDeque};
+use wasm_bindgen::JsValue;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+const HISTORY_LEN: usize = 60;
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct DiskIoInfo {
+    device: String,
+    reads: f64,
+    writes: f64,
+    read_bytes: f64,
+    write_bytes: f64,
+    iops: f64,
+    utilization: f64,
+    latency: f64,
+    queue_depth: f64,
+}
+
+#[derive(Default)]
+struct DeviceHistory {
+    read_bytes: VecDeque<f64>,
+    write_bytes: VecDeque<f64>,
+    utilization: VecDeque<f64>,
+}
+
+fn push_sample(values: &mut VecDeque<f64>, value: f64) {
+    if values.len() == HISTORY_LEN {
+        values.pop_front();
+    }
+    values.push_back(value);
+}
+
+#[function_component(DiskIo)]
+pub fn disk_io() -> Html {
+    let devices = use_state_eq(Vec::<DiskIoInfo>::new);
+    let history = use_mut_ref(HashMap::<String, DeviceHistory>::new);
+
+    let get_disk_io = use_async({
+        let devices = devices.clone();
+        let history = history.clone();
+        async move {
+            let result = invoke("disk_io", JsValue::NULL).await;
+            if let Ok(io) = from_value::<Vec<DiskIoInfo>>(result) {
+                let mut history = history.borrow_mut();
+                history.retain(|device, _| io.iter().any(|d| &d.device == device));
+                for device in &io {
+                    let entry = history.entry(device.device.clone()).or_default();
+                    push_sample(&mut entry.read_bytes, device.read_bytes);
+                    push_sample(&mut entry.write_bytes, device.write_bytes);
+                    push_sample(&mut entry.utilization, device.utilization);
+                }
+                devices.set(io);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((), {
+        let get_disk_io = get_disk_io.clone();
+        move |_| get_disk_io.run()
+    });
+
+    {
+        let get_disk_io = get_disk_io.clone();
+        use_interval(
+            move || {
+                get_disk_io.run();
+            },
+            1000,
+        )
+    }
+
+    let history = history.borrow();
+    let values = |values: &VecDeque<f64>| values.iter().cloned().collect::<Vec<f64>>();
+
+    html! {
+    <div class="flex flex-col">
+        <div class="overflow-x-auto">
+            <div class="inline-block min-w-full align-middle">
+                <div class="overflow-y-auto shadow h-screen">
+                    <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+                        <thead class="bg-gray-100 dark:bg-gray-700 sticky top-0">
+                            <tr>
+                                {for ["Device", "Read", "Write", "Reads/s", "Writes/s", "IOPS", "Utilization", "Latency", "Queue"].into_iter().map(|title| html!{
+                                <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                    {title}
+                                </th>
+                                })}
+                            </tr>
+                        </thead>
+                        <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                            {for devices.iter().map(|device| {
+                                let entry = history.get(&device.device);
+                                html!{
+                            <tr class="hover:bg-gray-100 dark:hover:bg-gray-700">
+                                <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{device.device.clone()}</td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                    <Sparkline values={entry.map(|e| values(&e.read_bytes)).unwrap_or_default()} color="#16a34a" />
+                                    <div>{format!("{}/s", format_size(device.read_bytes as u64))}</div>
+                                </td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                    <Sparkline values={entry.map(|e| values(&e.write_bytes)).unwrap_or_default()} />
+                                    <div>{format!("{}/s", format_size(device.write_bytes as u64))}</div>
+                                </td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{:.1}", device.reads)}</td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{:.1}", device.writes)}</td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{:.1}", device.iops)}</td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                    <Sparkline values={entry.map(|e| values(&e.utilization)).unwrap_or_default()} max={Some(100.0)} color="#dc2626" />
+                                    <div>{format!("{:.1} %", device.utilization)}</div>
+                                </td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{:.2} ms", device.latency)}</td>
+                                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{:.2}", device.queue_depth)}</td>
+                            </tr>
+                                }
+                            })}
+                        </tbody>
+                    </table>
+                </div>
+            </div>
+        </div>
+    </div>
+    }
+}
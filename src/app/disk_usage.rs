@@ -0,0 +1,210 @@
+use super::events::use_tauri_event;
+use super::{format_size, invoke};
+use crate::components::{
+    navbar::NavBar,
+    treemap::{Treemap, TreemapItem},
+};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use std::path::Path;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yew_router::hooks::use_location;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct AnalyzeQuery {
+    pub path: String,
+}
+
+#[derive(Serialize)]
+struct Args {
+    path: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct ScanProgress {
+    path: String,
+    files: u64,
+    directories: u64,
+    bytes: u64,
+    finished: bool,
+    cancelled: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct ScanNode {
+    path: String,
+    name: String,
+    size: u64,
+    files: u64,
+    is_dir: bool,
+    children: Vec<ScanNode>,
+}
+
+fn load_node(path: String, node: UseStateHandle<Option<ScanNode>>) {
+    spawn_local(async move {
+        let result = invoke("disk_scan_node", to_value(&Args { path }).unwrap()).await;
+        if let Ok(Some(scanned)) = from_value::<Option<ScanNode>>(result) {
+            node.set(Some(scanned));
+        }
+    });
+}
+
+#[function_component(DiskUsage)]
+pub fn disk_usage() -> Html {
+    let root = use_location()
+        .and_then(|location| location.query::<AnalyzeQuery>().ok())
+        .map(|query| query.path)
+        .unwrap_or_else(|| "/".to_string());
+    let progress = use_state(|| None::<ScanProgress>);
+    let node = use_state(|| None::<ScanNode>);
+
+    let ready = use_tauri_event("disk-scan-progress", {
+        let root = root.clone();
+        let progress = progress.clone();
+        let node = node.clone();
+        Callback::from(move |update: ScanProgress| {
+            if update.path != root {
+                return;
+            }
+            if update.finished && !update.cancelled {
+                load_node(root.clone(), node.clone());
+            }
+            progress.set(Some(update));
+        })
+    });
+
+    use_effect_with((ready, root.clone()), |(ready, root)| {
+        if *ready {
+            let path = root.clone();
+            spawn_local(async move {
+                invoke("analyze_disk", to_value(&Args { path }).unwrap()).await;
+            });
+        }
+    });
+
+    use_effect_with((), |_| {
+        || {
+            spawn_local(async {
+                invoke("cancel_disk_scan", JsValue::NULL).await;
+            })
+        }
+    });
+
+    let on_cancel = Callback::from(|_| {
+        spawn_local(async {
+            invoke("cancel_disk_scan", JsValue::NULL).await;
+        })
+    });
+
+    let select = {
+        let node = node.clone();
+        Callback::from(move |path: AttrValue| load_node(path.to_string(), node.clone()))
+    };
+
+    let on_up = {
+        let select = select.clone();
+        let root = root.clone();
+        let current = (*node).as_ref().map(|node| node.path.clone());
+        Callback::from(move |_| {
+            if let Some(parent) = current
+                .as_ref()
+                .filter(|current| **current != root)
+                .and_then(|current| Path::new(current).parent())
+            {
+                select.emit(AttrValue::from(parent.to_string_lossy().into_owned()));
+            }
+        })
+    };
+
+    let status = match &*progress {
+        Some(progress) => format!(
+            "{}{} files, {} directories, {}",
+            if progress.cancelled {
+                "Cancelled: "
+            } else if progress.finished {
+                ""
+            } else {
+                "Scanning: "
+            },
+            progress.files,
+            progress.directories,
+            format_size(progress.bytes)
+        ),
+        None => "Starting scan...".to_string(),
+    };
+    let scanning = progress.as_ref().is_none_or(|progress| !progress.finished);
+
+    html! {
+    <>
+        <NavBar />
+        <div class="absolute sticky top-0 p-4 bg-white block sm:flex items-center justify-between border-b border-gray-200  dark:bg-gray-800 dark:border-gray-700">
+            <div class="text-sm font-medium text-gray-800 dark:text-gray-300">
+                <div class="text-base">{format!("Disk usage of {}", root)}</div>
+                <div>{status}</div>
+            </div>
+            <div class="flex space-x-2">
+                <button onclick={on_up} disabled={node.as_ref().is_none_or(|node| node.path == root)} class="text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-sm px-5 py-2.5 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700">
+                    {"up"}
+                </button>
+                <button onclick={on_cancel} disabled={!scanning} class={format!("text-white bg-blue-700 hover:bg-blue-800 focus:ring-4 focus:ring-blue-300 font-medium rounded-lg text-sm px-5 py-2.5 dark:bg-blue-600 dark:hover:bg-blue-700 focus:outline-none dark:focus:ring-blue-800 {}", if scanning { "" } else { "opacity-30" })}>
+                    {"cancel"}
+                </button>
+            </div>
+        </div>
+        if let Some(current) = &*node {
+            <div class="p-4">
+                <div class="mb-2 text-sm font-medium text-gray-500 dark:text-gray-400">{format!("{} ({})", current.path, format_size(current.size))}</div>
+                <Treemap
+                    items={current.children.iter().map(|child| TreemapItem {
+                        key: AttrValue::from(child.path.clone()),
+                        label: AttrValue::from(format!("{} ({})", child.name, format_size(child.size))),
+                        value: child.size as f64,
+                        highlight: child.is_dir,
+                    }).collect::<Vec<_>>()}
+                    on_select={
+                        let select = select.clone();
+                        let dirs: Vec<String> = current.children.iter().filter(|child| child.is_dir).map(|child| child.path.clone()).collect();
+                        Callback::from(move |path: AttrValue| if dirs.iter().any(|dir| *dir == *path) { select.emit(path) })
+                    }
+                />
+            </div>
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+                <thead class="bg-gray-100 dark:bg-gray-700 sticky top-0">
+                    <tr>
+                        {for ["Name", "Size", "Files", ""].into_iter().map(|title| html!{
+                        <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                            {title}
+                        </th>
+                        })}
+                    </tr>
+                </thead>
+                <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                    {for current.children.iter().map(|child| {
+                        let percent = if current.size > 0 { child.size as f64 / current.size as f64 * 100.0 } else { 0.0 };
+                        let onclick = {
+                            let select = select.clone();
+                            let path = child.path.clone();
+                            let is_dir = child.is_dir;
+                            Callback::from(move |_| if is_dir { select.emit(AttrValue::from(path.clone())) })
+                        };
+                        html!{
+                    <tr {onclick} class={format!("hover:bg-gray-100 dark:hover:bg-gray-700 {}", if child.is_dir { "cursor-pointer" } else { "" })}>
+                        <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{if child.is_dir { format!("{}/", child.name) } else { child.name.clone() }}</td>
+                        <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format_size(child.size)}</td>
+                        <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{child.files}</td>
+                        <td class="px-2 py-1 w-1/3">
+                            <div class="w-full bg-gray-200 rounded-full h-2 dark:bg-gray-700">
+                                <div class="bg-blue-600 h-2 rounded-full" style={format!("width: {:.1}%", percent)}></div>
+                            </div>
+                        </td>
+                    </tr>
+                        }
+                    })}
+                </tbody>
+            </table>
+        }
+    </>
+    }
+}
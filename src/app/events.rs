@@ -0,0 +1,69 @@
+use js_sys::{Function, Reflect};
+use serde::de::DeserializeOwned;
+use serde_wasm_bindgen::from_value;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+use yew_hooks::use_latest;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn Fn(JsValue)>) -> JsValue;
+}
+
+// listens to a backend event while the component is mounted,
+// returns true once the listener is registered
+#[hook]
+pub fn use_tauri_event<T>(event: &'static str, callback: Callback<T>) -> bool
+where
+    T: DeserializeOwned + 'static,
+{
+    let ready = use_state(|| false);
+    let callback = use_latest(callback);
+
+    use_effect_with((), {
+        let ready = ready.clone();
+        move |_| {
+            let handler = Rc::new(Closure::<dyn Fn(JsValue)>::new(move |event: JsValue| {
+                let payload =
+                    Reflect::get(&event, &JsValue::from_str("payload")).unwrap_or(JsValue::NULL);
+                if let Ok(payload) = from_value::<T>(payload) {
+                    callback.current().emit(payload);
+                }
+            }));
+            let unlisten = Rc::new(RefCell::new(None::<Function>));
+            let mounted = Rc::new(Cell::new(true));
+
+            spawn_local({
+                let handler = handler.clone();
+                let unlisten = unlisten.clone();
+                let mounted = mounted.clone();
+                async move {
+                    let Ok(unlisten_fn) = listen(event, &handler).await.dyn_into::<Function>()
+                    else {
+                        return;
+                    };
+                    if mounted.get() {
+                        unlisten.replace(Some(unlisten_fn));
+                        ready.set(true);
+                    } else {
+                        let _ = unlisten_fn.call0(&JsValue::NULL);
+                    }
+                }
+            });
+
+            move || {
+                mounted.set(false);
+                if let Some(unlisten_fn) = unlisten.take() {
+                    let _ = unlisten_fn.call0(&JsValue::NULL);
+                }
+                drop(handler);
+            }
+        }
+    });
+
+    *ready
+}
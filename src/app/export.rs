@@ -0,0 +1,122 @@
+use super::try_invoke;
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+pub struct TimeRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportSource {
+    Processes { name: Option<String> },
+    FileSystems { hide_pseudo: bool },
+    History { range: TimeRange, resolution: u64 },
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum ExportFormat {
+    Csv,
+    Json,
+    JsonLines,
+}
+
+const FORMATS: [(ExportFormat, &str); 3] = [
+    (ExportFormat::Csv, "CSV"),
+    (ExportFormat::Json, "JSON"),
+    (ExportFormat::JsonLines, "JSON lines"),
+];
+
+#[derive(Serialize)]
+struct Args {
+    source: ExportSource,
+    format: ExportFormat,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ExportProps {
+    // labelled sources to choose from, the first one is preselected
+    pub sources: Vec<(AttrValue, ExportSource)>,
+}
+
+// format and source pickers next to a button opening the save dialog
+#[function_component(Export)]
+pub fn export(props: &ExportProps) -> Html {
+    let source = use_state(|| 0usize);
+    let format = use_state(|| ExportFormat::Csv);
+    let message = use_state(|| None::<String>);
+
+    let selected_index = |e: Event| {
+        e.target_unchecked_into::<HtmlSelectElement>()
+            .selected_index()
+            .max(0) as usize
+    };
+    let on_source = {
+        let source = source.clone();
+        Callback::from(move |e: Event| source.set(selected_index(e)))
+    };
+    let on_format = {
+        let format = format.clone();
+        Callback::from(move |e: Event| format.set(FORMATS[selected_index(e).min(2)].0))
+    };
+
+    let on_export = {
+        let message = message.clone();
+        let selected = props
+            .sources
+            .get(*source)
+            .or(props.sources.first())
+            .map(|(_, source)| source.clone());
+        let format = *format;
+        Callback::from(move |_| {
+            let Some(source) = selected.clone() else {
+                return;
+            };
+            let message = message.clone();
+            spawn_local(async move {
+                let args = to_value(&Args { source, format }).unwrap();
+                match try_invoke("export", args).await {
+                    Ok(path) => {
+                        if let Ok(Some(path)) = from_value::<Option<String>>(path) {
+                            message.set(Some(format!("saved to {}", path)));
+                        }
+                    }
+                    Err(error) => message.set(Some(
+                        from_value::<String>(error).unwrap_or_else(|_| "export failed".into()),
+                    )),
+                }
+            });
+        })
+    };
+
+    let select_class = "bg-gray-50 border border-gray-300 text-gray-900 text-sm rounded-lg p-2 dark:bg-gray-700 dark:border-gray-600 dark:text-white";
+
+    html! {
+        <div class="flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300">
+            if props.sources.len() > 1 {
+                <select class={select_class} onchange={on_source}>
+                    {for props.sources.iter().enumerate().map(|(i, (label, _))| html!{
+                        <option selected={i == *source}>{label.clone()}</option>
+                    })}
+                </select>
+            }
+            <select class={select_class} onchange={on_format}>
+                {for FORMATS.iter().map(|(value, label)| html!{
+                    <option selected={*value == *format}>{*label}</option>
+                })}
+            </select>
+            <button onclick={on_export} class="text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-sm px-3 py-2 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700">
+                {"export"}
+            </button>
+            if let Some(text) = &*message {
+                <span class="whitespace-nowrap">{text.clone()}</span>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,139 @@
+use super::hosts::is_remote;
+use super::{invoke, tauri_invoke, tauri_try_invoke, try_invoke};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub(super) struct ExporterConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+#[derive(Serialize)]
+struct Args {
+    config: ExporterConfig,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ExporterSettingsProps {
+    // this machine's even while a remote host is shown, otherwise the shown host's
+    #[prop_or_default]
+    pub this_machine: bool,
+}
+
+// turns the prometheus endpoint on or off and picks its port
+#[function_component(ExporterSettings)]
+pub fn exporter_settings(props: &ExporterSettingsProps) -> Html {
+    let this_machine = props.this_machine;
+    let config = use_state(|| None::<ExporterConfig>);
+    let port = use_state(|| 0u16);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let config = config.clone();
+        let port = port.clone();
+        move |_| {
+            spawn_local(async move {
+                let result = if this_machine {
+                    tauri_invoke("exporter_config", JsValue::NULL).await
+                } else {
+                    invoke("exporter_config", JsValue::NULL).await
+                };
+                if let Ok(current) = from_value::<ExporterConfig>(result) {
+                    port.set(current.port);
+                    config.set(Some(current));
+                }
+            });
+        }
+    });
+
+    let apply = {
+        let config = config.clone();
+        let error = error.clone();
+        Callback::from(move |next: ExporterConfig| {
+            let config = config.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let args = to_value(&Args { config: next }).unwrap();
+                let result = if this_machine {
+                    tauri_try_invoke("set_exporter_config", args).await
+                } else {
+                    try_invoke("set_exporter_config", args).await
+                };
+                match result {
+                    Ok(_) => {
+                        config.set(Some(next));
+                        error.set(None);
+                    }
+                    Err(err) => error.set(Some(
+                        from_value::<String>(err).unwrap_or_else(|_| "could not start".into()),
+                    )),
+                }
+            });
+        })
+    };
+
+    let Some(current) = *config else {
+        return html! {};
+    };
+
+    let on_toggle = {
+        let apply = apply.clone();
+        let port = *port;
+        Callback::from(move |_| {
+            apply.emit(ExporterConfig {
+                enabled: !current.enabled,
+                port,
+            })
+        })
+    };
+    let on_port = {
+        let port = port.clone();
+        let apply = apply.clone();
+        Callback::from(move |e: Event| {
+            let Ok(value) = e
+                .target_unchecked_into::<HtmlInputElement>()
+                .value()
+                .parse()
+            else {
+                return;
+            };
+            port.set(value);
+            if current.enabled {
+                apply.emit(ExporterConfig {
+                    enabled: true,
+                    port: value,
+                });
+            }
+        })
+    };
+
+    let remote = !this_machine && is_remote();
+    html! {
+        <div class="p-4 bg-white flex items-center space-x-6 border-b border-gray-200 text-sm font-medium text-gray-800 dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300">
+            <label class="inline-flex items-center cursor-pointer">
+                <input type="checkbox" checked={current.enabled} onchange={on_toggle} class="w-4 h-4 me-2" />
+                {if remote { "Prometheus exporter of the remote host" } else { "Prometheus exporter" }}
+            </label>
+            <label class="inline-flex items-center">
+                {"Port"}
+                <input type="number" min="1" max="65535" value={port.to_string()} onchange={on_port} class="w-24 mx-2 p-1 bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600" />
+            </label>
+            if current.enabled {
+                <span class="text-gray-500 dark:text-gray-400">
+                    {format!("http://127.0.0.1:{}/metrics", current.port)}
+                    if remote {
+                        {" on the remote host"}
+                    }
+                </span>
+            }
+            if let Some(message) = &*error {
+                <span class="text-red-600 dark:text-red-500">{message.clone()}</span>
+            }
+        </div>
+    }
+}
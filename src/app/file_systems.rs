@@ -1,10 +1,29 @@
-use serde::Deserialize;
-use serde_wasm_bindgen::from_value;
-use wasm_bindgen::JsValue;
-use yew::{function_component, html, use_effect, use_effect_with, use_state, Html};
-use yew_hooks::use_async;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use web_sys::HtmlInputElement;
+use yew::{
+    function_component, html, use_effect, use_effect_with, use_state, AttrValue, Callback, Event,
+    Html, TargetCast, UseStateHandle,
+};
+use yew_hooks::{use_async, use_interval};
 
-use crate::{app::invoke, components::navbar::NavBar};
+use super::disk_io::DiskIo;
+use super::disk_usage::AnalyzeQuery;
+use super::events::use_tauri_event;
+use super::export::{Export, ExportSource};
+use super::mount_notifications::MountChange;
+use super::removal::RemovalActions;
+use crate::{
+    app::{invoke, Route},
+    components::navbar::NavBar,
+};
+use yew_router::hooks::use_navigator;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Args {
+    hide_pseudo: Option<bool>,
+}
 
 #[derive(Deserialize)]
 struct DiskInfo {
@@ -14,16 +33,61 @@ struct DiskInfo {
     total: String,
     available: String,
     used: String,
+    inodes_total: u64,
+    inodes_used: u64,
+    inodes_free: u64,
+    read_only: bool,
+    removable: bool,
+    kind: String,
+    options: String,
+    percent_used: f64,
+}
+
+fn usage_color(percent: f64, warning: f64, critical: f64) -> &'static str {
+    if percent >= critical {
+        "bg-red-600"
+    } else if percent >= warning {
+        "bg-yellow-400"
+    } else {
+        "bg-green-600"
+    }
+}
+
+fn inode_percent(file_system: &DiskInfo) -> f64 {
+    if file_system.inodes_total == 0 {
+        0.0
+    } else {
+        file_system.inodes_used as f64 / file_system.inodes_total as f64 * 100.0
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    FileSystems,
+    DiskIo,
 }
 
 #[function_component(FileSystems)]
 pub fn file_systems() -> Html {
     let file_systems_state = use_state(|| Vec::new());
+    let tab = use_state(|| Tab::FileSystems);
+    let navigator = use_navigator().unwrap();
+    let hide_pseudo = use_state(|| true);
+    let warning = use_state(|| 80.0);
+    let critical = use_state(|| 90.0);
     let get_file_systems = use_async({
         let file_systems_state = file_systems_state.clone();
+        let hide_pseudo = *hide_pseudo;
 
         async move {
-            let result = invoke("file_systems", JsValue::NULL).await;
+            let result = invoke(
+                "file_systems",
+                to_value(&Args {
+                    hide_pseudo: Some(hide_pseudo),
+                })
+                .unwrap(),
+            )
+            .await;
             if let Ok(get_file_sys) = from_value::<Vec<DiskInfo>>(result) {
                 file_systems_state.set(get_file_sys)
             }
@@ -32,10 +96,82 @@ pub fn file_systems() -> Html {
         }
     });
 
-    use_effect_with((), move |_| get_file_systems.run());
+    use_effect_with(*hide_pseudo, {
+        let get_file_systems = get_file_systems.clone();
+        move |_| get_file_systems.run()
+    });
+
+    {
+        let get_file_systems = get_file_systems.clone();
+        use_interval(
+            move || {
+                get_file_systems.run();
+            },
+            2000,
+        )
+    }
+
+    use_tauri_event("mounts-changed", {
+        let get_file_systems = get_file_systems.clone();
+        Callback::from(move |_: MountChange| get_file_systems.run())
+    });
+
+    let on_hide_pseudo = Callback::from({
+        let hide_pseudo = hide_pseudo.clone();
+        move |_| hide_pseudo.set(!*hide_pseudo)
+    });
+    let on_threshold = |threshold: UseStateHandle<f64>| {
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(value) = input.value().parse::<f64>() {
+                threshold.set(value.clamp(0.0, 100.0));
+            }
+        })
+    };
+
+    let active_class = "cursor-pointer inline-block p-2 text-blue-600 border-b-2 border-blue-600 rounded-t-lg active dark:text-blue-500 dark:border-blue-500";
+    let passive_class = "cursor-pointer inline-block p-2 border-b-2 border-transparent rounded-t-lg hover:text-gray-600 hover:border-gray-300 dark:hover:text-gray-300";
+    let tab_link = |target: Tab, label: &'static str| {
+        let onclick = {
+            let tab = tab.clone();
+            Callback::from(move |_| tab.set(target))
+        };
+        html! {
+            <li class="me-1">
+                <a {onclick} class={if *tab == target { active_class } else { passive_class }}>{label}</a>
+            </li>
+        }
+    };
+
     html! {
     <>
         <NavBar />
+        <ul class="flex flex-wrap px-4 -mb-px text-sm font-medium text-center text-gray-500 border-b border-gray-200 dark:text-gray-400 dark:border-gray-700">
+            {tab_link(Tab::FileSystems, "File Systems")}
+            {tab_link(Tab::DiskIo, "Disk I/O")}
+        </ul>
+        if *tab == Tab::DiskIo {
+            <DiskIo />
+        } else {
+        <div class="p-4 bg-white flex items-center space-x-6 border-b border-gray-200 text-sm font-medium text-gray-800 dark:bg-gray-800 dark:border-gray-700 dark:text-gray-300">
+            <label class="inline-flex items-center cursor-pointer">
+                <input type="checkbox" checked={*hide_pseudo} onchange={on_hide_pseudo} class="w-4 h-4 me-2" />
+                {"Hide pseudo file systems"}
+            </label>
+            <label class="inline-flex items-center">
+                {"Warning at"}
+                <input type="number" min="0" max="100" value={warning.to_string()} onchange={on_threshold(warning.clone())} class="w-16 mx-2 p-1 bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600" />
+                {"%"}
+            </label>
+            <label class="inline-flex items-center">
+                {"Critical at"}
+                <input type="number" min="0" max="100" value={critical.to_string()} onchange={on_threshold(critical.clone())} class="w-16 mx-2 p-1 bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600" />
+                {"%"}
+            </label>
+            <div class="ms-auto">
+                <Export sources={vec![(AttrValue::from("file systems"), ExportSource::FileSystems { hide_pseudo: *hide_pseudo })]} />
+            </div>
+        </div>
                                          <div class="flex flex-col">
                                  <div class="overflow-x-auto">
                                      <div class="inline-block min-w-full align-middle">
@@ -61,6 +197,29 @@ pub fn file_systems() -> Html {
                                                          <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
                                      {"Used"}
                                                          </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Usage"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Inodes Used"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Inodes Free"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Mode"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Kind"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Removable"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {"Options"}
+                                                         </th>
+                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                                         </th>
                                                      </tr>
                                                  </thead>
                                                  <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
@@ -73,6 +232,39 @@ pub fn file_systems() -> Html {
                                                      <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{file_system.total.clone()}</td>
                                                      <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{file_system.available.clone()}</td>
                                                      <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{file_system.used.clone()}</td>
+                                                     <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                                         <div class="w-32 bg-gray-200 rounded-full h-2 dark:bg-gray-700">
+                                                             <div class={format!("h-2 rounded-full {}", usage_color(file_system.percent_used, *warning, *critical))} style={format!("width: {:.1}%", file_system.percent_used)}></div>
+                                                         </div>
+                                                         <div>{format!("{:.1} %", file_system.percent_used)}</div>
+                                                     </td>
+                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{} / {} ({:.0} %)", file_system.inodes_used, file_system.inodes_total, inode_percent(file_system))}</td>
+                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{file_system.inodes_free}</td>
+                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{if file_system.read_only { "ro" } else { "rw" }}</td>
+                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{file_system.kind.clone()}</td>
+                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{if file_system.removable { "yes" } else { "no" }}</td>
+                                                     <td class="cursor-pointer px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{file_system.options.clone()}</td>
+                                                     <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                                         <button onclick={{
+                                                             let navigator = navigator.clone();
+                                                             let path = file_system.directory.clone();
+                                                             Callback::from(move |_| { let _ = navigator.push_with_query(&Route::DiskUsage, &AnalyzeQuery { path: path.clone() }); })
+                                                         }} class="text-white bg-blue-700 hover:bg-blue-800 focus:ring-4 focus:ring-blue-300 font-medium rounded-lg text-xs px-3 py-1 dark:bg-blue-600 dark:hover:bg-blue-700 focus:outline-none dark:focus:ring-blue-800">
+                                                             {"analyze"}
+                                                         </button>
+                                                         if file_system.removable {
+                                                             <span class="ms-1">
+                                                                 <RemovalActions
+                                                                     directory={file_system.directory.clone()}
+                                                                     device={file_system.device.clone()}
+                                                                     on_done={{
+                                                                         let get_file_systems = get_file_systems.clone();
+                                                                         Callback::from(move |_| get_file_systems.run())
+                                                                     }}
+                                                                 />
+                                                             </span>
+                                                         }
+                                                     </td>
                                                  </tr>
                                                      })}
                                                  </tbody>
@@ -83,6 +275,7 @@ pub fn file_systems() -> Html {
                              </div>
                              <div class="sticky bottom-0 right-0 items-center w-full p-4 bg-gray-100 border-t border-gray-200 sm:flex sm:justify-between dark:bg-gray-800 dark:border-gray-700">
                          </div>
+        }
 
     {"file systems"}
     </>
@@ -0,0 +1,240 @@
+use super::{tauri_invoke, tauri_try_invoke, Route};
+use js_sys::{Object, Reflect};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use std::cell::Cell;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_router::Routable;
+
+// the commands a remote agent serves, export dialogs, alerts and leaks stay local
+const REMOTE_COMMANDS: [&str; 22] = [
+    "os_info",
+    "process_info",
+    "process_threads",
+    "process_history",
+    "kill_process",
+    "signal_process",
+    "file_systems",
+    "unmount",
+    "eject",
+    "analyze_disk",
+    "cancel_disk_scan",
+    "disk_scan_node",
+    "disk_io",
+    "get_metrics",
+    "network_interfaces",
+    "listening_ports",
+    "history",
+    "history_bounds",
+    "history_snapshot",
+    "history_diff",
+    "exporter_config",
+    "set_exporter_config",
+];
+
+thread_local! {
+    // set once before the pages render, switching hosts reloads the window
+    static REMOTE: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn is_remote() -> bool {
+    REMOTE.with(Cell::get)
+}
+
+// sends `cmd` through the active remote host when it is one the agent answers
+pub(super) fn route(cmd: &str, args: JsValue) -> (&str, JsValue) {
+    if !is_remote() || !REMOTE_COMMANDS.contains(&cmd) {
+        return (cmd, args);
+    }
+    let wrapped = Object::new();
+    let _ = Reflect::set(&wrapped, &"command".into(), &cmd.into());
+    let _ = Reflect::set(&wrapped, &"args".into(), &args);
+    ("remote_invoke", wrapped.into())
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct HostInfo {
+    id: String,
+    name: String,
+    url: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Default)]
+struct HostList {
+    hosts: Vec<HostInfo>,
+    active: Option<String>,
+}
+
+#[derive(Serialize)]
+struct AddArgs {
+    name: String,
+    url: String,
+    token: String,
+}
+
+#[derive(Serialize)]
+struct IdArgs {
+    id: Option<String>,
+}
+
+async fn host_list() -> HostList {
+    from_value(tauri_invoke("hosts", JsValue::NULL).await).unwrap_or_default()
+}
+
+// reads which host is active, the app renders its pages after this
+pub async fn load() {
+    let list = host_list().await;
+    REMOTE.with(|remote| remote.set(list.active.is_some()));
+}
+
+async fn switch_host(id: Option<String>) -> Result<(), String> {
+    let args = to_value(&IdArgs { id }).unwrap();
+    tauri_try_invoke("set_active_host", args)
+        .await
+        .map(|_| ())
+        .map_err(|err| from_value::<String>(err).unwrap_or_else(|_| "could not connect".into()))
+}
+
+// pages keep state from the previous host, so they start over after a switch
+fn reload() {
+    if let Some(window) = web_sys::window() {
+        let _ = window.location().reload();
+    }
+}
+
+// makes `id` the active host and loads `route` for it
+pub fn open_on_host(id: Option<String>, route: Route) {
+    spawn_local(async move {
+        if switch_host(id).await.is_ok() {
+            if let Some(window) = web_sys::window() {
+                let _ = window.location().set_href(&route.to_path());
+            }
+        }
+    });
+}
+
+// picks the machine every page shows, and adds or removes remote agents
+#[function_component(HostSwitcher)]
+pub fn host_switcher() -> Html {
+    let list = use_state(HostList::default);
+    let adding = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let name_input = use_node_ref();
+    let url_input = use_node_ref();
+    let token_input = use_node_ref();
+
+    use_effect_with((), {
+        let list = list.clone();
+        move |_| {
+            spawn_local(async move {
+                list.set(host_list().await);
+            });
+        }
+    });
+
+    let on_select = {
+        let error = error.clone();
+        Callback::from(move |e: Event| {
+            let value = e.target_unchecked_into::<HtmlInputElement>().value();
+            let id = (!value.is_empty()).then_some(value);
+            let error = error.clone();
+            spawn_local(async move {
+                match switch_host(id).await {
+                    Ok(()) => reload(),
+                    Err(message) => error.set(Some(message)),
+                }
+            });
+        })
+    };
+
+    let on_add = {
+        let adding = adding.clone();
+        let error = error.clone();
+        let name_input = name_input.clone();
+        let url_input = url_input.clone();
+        let token_input = token_input.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let value = |input: &NodeRef| input.cast::<HtmlInputElement>().unwrap().value();
+            let args = AddArgs {
+                name: value(&name_input),
+                url: value(&url_input),
+                token: value(&token_input),
+            };
+            let adding = adding.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let result = tauri_try_invoke("add_host", to_value(&args).unwrap()).await;
+                let added = match result.map(from_value::<HostInfo>) {
+                    Ok(Ok(host)) => host,
+                    Ok(Err(_)) => return,
+                    Err(err) => {
+                        error.set(from_value::<String>(err).ok());
+                        return;
+                    }
+                };
+                adding.set(false);
+                if switch_host(Some(added.id)).await.is_ok() {
+                    reload();
+                }
+            });
+        })
+    };
+
+    let on_remove = {
+        let active = list.active.clone();
+        Callback::from(move |_| {
+            let Some(id) = active.clone() else {
+                return;
+            };
+            spawn_local(async move {
+                let args = to_value(&IdArgs { id: Some(id) }).unwrap();
+                tauri_invoke("remove_host", args).await;
+                reload();
+            });
+        })
+    };
+
+    let toggle_adding = {
+        let adding = adding.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            adding.set(!*adding);
+            error.set(None);
+        })
+    };
+
+    let active = list.active.clone().unwrap_or_default();
+    let input_class = "p-1 text-sm bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600 dark:text-white";
+
+    html! {
+        <div class="absolute right-2 top-2 flex items-center space-x-2 text-sm">
+            <select onchange={on_select} class={input_class}>
+                <option value="" selected={active.is_empty()}>{"This machine"}</option>
+                {for list.hosts.iter().map(|host| html! {
+                    <option value={host.id.clone()} selected={host.id == active} title={host.url.clone()}>{host.name.clone()}</option>
+                })}
+            </select>
+            if list.active.is_some() {
+                <button onclick={on_remove} class="px-2 py-1 text-gray-600 hover:text-red-600 dark:text-gray-300">{"Remove"}</button>
+            }
+            <button onclick={toggle_adding} class="px-2 py-1 text-gray-600 hover:text-blue-600 dark:text-gray-300">{if *adding { "Cancel" } else { "Add host" }}</button>
+            if *adding {
+                <form onsubmit={on_add} class="absolute right-0 top-10 p-3 w-80 flex flex-col space-y-2 bg-white border border-gray-200 rounded-lg shadow dark:bg-gray-800 dark:border-gray-700">
+                    <input ref={name_input} required=true placeholder="Name" class={input_class} />
+                    <input ref={url_input} required=true placeholder="wss://host:7878" class={input_class} />
+                    <input ref={token_input} required=true type="password" placeholder="Token" class={input_class} />
+                    if let Some(message) = &*error {
+                        <span class="text-red-600 dark:text-red-500">{message.clone()}</span>
+                    }
+                    <button type="submit" class="text-white bg-blue-700 hover:bg-blue-800 rounded-lg px-3 py-1.5 dark:bg-blue-600 dark:hover:bg-blue-700">{"Connect"}</button>
+                </form>
+            } else if let Some(message) = &*error {
+                <span class="text-red-600 dark:text-red-500">{message.clone()}</span>
+            }
+        </div>
+    }
+}
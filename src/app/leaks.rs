@@ -0,0 +1,307 @@
+use super::hosts::is_remote;
+use super::process_details::ProcessDetails;
+use super::processes::ProccessInfo;
+use super::time_travel::format_timestamp;
+use super::{format_size, invoke, try_invoke};
+use crate::components::{navbar::NavBar, sparkline::Sparkline};
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+const MB: f64 = 1024.0 * 1024.0;
+// the watcher only learns something new with every history record
+const POLL_MS: u32 = 5000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+struct LeakSettings {
+    window_secs: u64,
+    // bytes per hour
+    min_growth: u64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Leak {
+    pid: u32,
+    name: String,
+    memory: u64,
+    growth: u64,
+    since: u64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct MemoryPoint {
+    timestamp: u64,
+    memory: u64,
+}
+
+#[derive(Serialize)]
+struct SettingsArgs {
+    settings: LeakSettings,
+}
+
+#[derive(Serialize)]
+struct PidArgs {
+    pid: u32,
+}
+
+#[derive(Serialize)]
+struct NameArgs {
+    name: Option<String>,
+}
+
+fn growth_text(growth: u64) -> String {
+    format!("{}/h", format_size(growth))
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MemoryChartProps {
+    pub pid: u32,
+}
+
+// the recorded memory of a process over the leak window, with its growth if it is flagged
+#[function_component(MemoryChart)]
+pub fn memory_chart(props: &MemoryChartProps) -> Html {
+    let points = use_state_eq(Vec::<MemoryPoint>::new);
+    let leak = use_state_eq(|| None::<Leak>);
+    let pid = props.pid;
+
+    let get_memory = use_async({
+        let points = points.clone();
+        let leak = leak.clone();
+        async move {
+            let result = invoke("process_memory", to_value(&PidArgs { pid }).unwrap()).await;
+            if let Ok(current) = from_value::<Vec<MemoryPoint>>(result) {
+                points.set(current);
+            }
+            if let Ok(leaks) = from_value::<Vec<Leak>>(invoke("memory_leaks", JsValue::NULL).await)
+            {
+                leak.set(leaks.into_iter().find(|leak| leak.pid == pid));
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with(pid, {
+        let get_memory = get_memory.clone();
+        move |_| get_memory.run()
+    });
+
+    {
+        let get_memory = get_memory.clone();
+        use_interval(
+            move || {
+                get_memory.run();
+            },
+            POLL_MS,
+        )
+    }
+
+    let values: Vec<f64> = points.iter().map(|point| point.memory as f64).collect();
+    let low = points.iter().map(|point| point.memory).min().unwrap_or(0);
+    let high = points.iter().map(|point| point.memory).max().unwrap_or(0);
+
+    html! {
+        <div class="px-4 py-2 text-sm text-gray-800 dark:text-gray-300">
+            <div class="mb-1 flex justify-between text-gray-500 dark:text-gray-400">
+                <span>{"Memory"}</span>
+                if let Some(first) = points.first() {
+                    <span>{format!("since {}", format_timestamp(first.timestamp))}</span>
+                }
+            </div>
+            if points.len() < 2 {
+                <span class="text-gray-500 dark:text-gray-400">{"Not recorded long enough."}</span>
+            } else {
+                <Sparkline {values} width={360} height={80} />
+                <div class="flex justify-between text-xs text-gray-500 dark:text-gray-400">
+                    <span>{format!("low {}", format_size(low))}</span>
+                    <span>{format!("high {}", format_size(high))}</span>
+                </div>
+            }
+            if let Some(leak) = &*leak {
+                <div class="mt-1 text-red-600 dark:text-red-500">{format!("Growing steadily by {}", growth_text(leak.growth))}</div>
+            }
+        </div>
+    }
+}
+
+// processes whose memory only went up over the window, from this machine's history
+#[function_component(Leaks)]
+pub fn leaks() -> Html {
+    let settings = use_state_eq(|| None::<LeakSettings>);
+    let leaks = use_state_eq(Vec::<Leak>::new);
+    let selected = use_state_eq(|| None::<(u32, String)>);
+    let details = use_state_eq(|| None::<ProccessInfo>);
+    let error = use_state(|| None::<String>);
+    let window_input = use_node_ref();
+    let growth_input = use_node_ref();
+
+    let get_settings = use_async({
+        let settings = settings.clone();
+        async move {
+            let result = invoke("leak_settings", JsValue::NULL).await;
+            if let Ok(current) = from_value::<LeakSettings>(result) {
+                settings.set(Some(current));
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    let get_leaks = use_async({
+        let leaks = leaks.clone();
+        let details = details.clone();
+        let selected = (*selected).clone();
+        async move {
+            let result = invoke("memory_leaks", JsValue::NULL).await;
+            if let Ok(current) = from_value::<Vec<Leak>>(result) {
+                leaks.set(current);
+            }
+            // the details panel wants the live numbers of the selected process
+            if let Some((pid, name)) = selected {
+                let args = to_value(&NameArgs { name: Some(name) }).unwrap();
+                if let Ok(processes) =
+                    from_value::<Vec<ProccessInfo>>(invoke("process_info", args).await)
+                {
+                    details.set(
+                        processes
+                            .into_iter()
+                            .find(|process| process.id == pid.to_string()),
+                    );
+                }
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((), {
+        let get_settings = get_settings.clone();
+        move |_| get_settings.run()
+    });
+
+    use_effect_with((*selected).clone(), {
+        let get_leaks = get_leaks.clone();
+        move |_| get_leaks.run()
+    });
+
+    {
+        let get_leaks = get_leaks.clone();
+        use_interval(
+            move || {
+                get_leaks.run();
+            },
+            POLL_MS,
+        )
+    }
+
+    let on_save = {
+        let get_settings = get_settings.clone();
+        let get_leaks = get_leaks.clone();
+        let error = error.clone();
+        let window_input = window_input.clone();
+        let growth_input = growth_input.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let value = |input: &NodeRef| {
+                input
+                    .cast::<HtmlInputElement>()
+                    .and_then(|input| input.value().trim().parse::<f64>().ok())
+            };
+            let (Some(minutes), Some(growth)) = (value(&window_input), value(&growth_input)) else {
+                error.set(Some("the window and the growth have to be numbers".into()));
+                return;
+            };
+            let settings = LeakSettings {
+                window_secs: (minutes * 60.0) as u64,
+                min_growth: (growth * MB) as u64,
+            };
+            let get_settings = get_settings.clone();
+            let get_leaks = get_leaks.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let args = to_value(&SettingsArgs { settings }).unwrap();
+                match try_invoke("set_leak_settings", args).await {
+                    Ok(_) => error.set(None),
+                    Err(err) => error.set(from_value::<String>(err).ok()),
+                }
+                get_settings.run();
+                get_leaks.run();
+            });
+        })
+    };
+
+    let on_close_details = {
+        let selected = selected.clone();
+        let details = details.clone();
+        Callback::from(move |_| {
+            selected.set(None);
+            details.set(None);
+        })
+    };
+
+    let input_class = "p-2 w-24 text-sm bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600 dark:text-white";
+    let cell_class =
+        "px-4 py-2 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300";
+    let header_class =
+        "p-4 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400";
+
+    html! {
+    <>
+        <NavBar />
+        if is_remote() {
+            <div class="px-4 pt-4 text-sm text-gray-500 dark:text-gray-400">{"Memory leaks are found in this machine's history, not the selected remote host's."}</div>
+        }
+        if let Some(current) = *settings {
+            <form onsubmit={on_save} class="px-4 py-4 flex flex-wrap items-center gap-2 text-sm text-gray-800 dark:text-gray-300">
+                {"Flag processes growing by at least"}
+                <input ref={growth_input} type="number" step="any" min="0" value={format!("{}", current.min_growth as f64 / MB)} class={input_class} />
+                {"MB per hour without dropping, over the last"}
+                <input ref={window_input} type="number" step="any" min="1" value={format!("{}", current.window_secs as f64 / 60.0)} class={input_class} />
+                {"minutes"}
+                <button type="submit" class="text-white bg-blue-700 hover:bg-blue-800 rounded-lg text-sm px-4 py-2 dark:bg-blue-600 dark:hover:bg-blue-700">{"Save"}</button>
+                if let Some(message) = &*error {
+                    <span class="text-red-600 dark:text-red-500">{message.clone()}</span>
+                }
+            </form>
+        }
+        <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+            <thead class="bg-gray-100 dark:bg-gray-700">
+                <tr>
+                    <th class={header_class}>{"Name"}</th>
+                    <th class={header_class}>{"ID"}</th>
+                    <th class={header_class}>{"Memory"}</th>
+                    <th class={header_class}>{"Growth"}</th>
+                    <th class={header_class}>{"Watched since"}</th>
+                </tr>
+            </thead>
+            <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                {for leaks.iter().map(|leak| {
+                    let onclick = {
+                        let selected = selected.clone();
+                        let target = (leak.pid, leak.name.clone());
+                        Callback::from(move |_| selected.set(Some(target.clone())))
+                    };
+                    let highlighted = selected.as_ref().is_some_and(|(pid, _)| *pid == leak.pid);
+                    html! {
+                        <tr key={leak.pid} {onclick} class={classes!("cursor-pointer", "hover:bg-gray-100", "dark:hover:bg-gray-700", highlighted.then_some("bg-gray-100 dark:bg-gray-700"))}>
+                            <td class={cell_class}>{leak.name.clone()}</td>
+                            <td class={cell_class}>{leak.pid}</td>
+                            <td class={cell_class}>{format_size(leak.memory)}</td>
+                            <td class={classes!(cell_class, "text-red-600", "dark:text-red-500")}>{growth_text(leak.growth)}</td>
+                            <td class={cell_class}>{format_timestamp(leak.since)}</td>
+                        </tr>
+                    }
+                })}
+            </tbody>
+        </table>
+        if leaks.is_empty() {
+            <div class="px-4 py-2 text-sm text-gray-500 dark:text-gray-400">{"No process is growing steadily."}</div>
+        }
+        if let Some(process) = (*details).clone() {
+            <ProcessDetails {process} on_close={on_close_details} />
+        }
+    </>
+    }
+}
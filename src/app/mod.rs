@@ -1,16 +1,81 @@
+mod alerts;
+mod columns;
+mod disk_io;
+mod disk_usage;
+mod events;
+mod export;
+mod exporter;
 mod file_systems;
+mod hosts;
+mod leaks;
+mod mount_notifications;
+mod network;
+mod overview;
+mod ports;
+mod process_details;
+mod process_history;
 mod processes;
+mod removal;
+mod settings;
+mod time_travel;
 
+use alerts::Alerts;
+use disk_usage::DiskUsage;
 use file_systems::FileSystems;
+pub use hosts::HostSwitcher;
+use leaks::Leaks;
+use mount_notifications::MountNotifications;
+use network::Network;
+use overview::Overview;
+use ports::Ports;
 use processes::Processes;
+use settings::SettingsPage;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
-use yew::{function_component, html, Html};
+use wasm_bindgen_futures::spawn_local;
+use yew::{function_component, html, use_effect_with, use_state, Html};
 use yew_router::{BrowserRouter, Routable, Switch};
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke)]
+    async fn tauri_invoke(cmd: &str, args: JsValue) -> JsValue;
+
+    // like invoke, but a command returning Err rejects instead of throwing
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+    async fn tauri_try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+// the pages call these, they answer from the active host
+async fn invoke(cmd: &str, args: JsValue) -> JsValue {
+    let (cmd, args) = hosts::route(cmd, args);
+    tauri_invoke(cmd, args).await
+}
+
+async fn try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue> {
+    let (cmd, args) = hosts::route(cmd, args);
+    tauri_try_invoke(cmd, args).await
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    const MB: f64 = KB * 1024.0;
+    const GB: f64 = MB * 1024.0;
+    const TB: f64 = GB * 1024.0;
+
+    let size = bytes as f64;
+    let (value, unit) = if size >= TB {
+        (size / TB, "TB")
+    } else if size >= GB {
+        (size / GB, "GB")
+    } else if size >= MB {
+        (size / MB, "MB")
+    } else if size >= KB {
+        (size / KB, "KB")
+    } else {
+        (size, "bytes")
+    };
+
+    format!("{:.2} {}", value, unit)
 }
 
 #[derive(Clone, Routable, PartialEq)]
@@ -19,6 +84,20 @@ pub enum Route {
     Processes,
     #[at("/file_suystems")]
     FileSystems,
+    #[at("/disk_usage")]
+    DiskUsage,
+    #[at("/network")]
+    Network,
+    #[at("/ports")]
+    Ports,
+    #[at("/overview")]
+    Overview,
+    #[at("/alerts")]
+    Alerts,
+    #[at("/leaks")]
+    Leaks,
+    #[at("/settings")]
+    Settings,
 }
 
 fn switch(routes: Route) -> Html {
@@ -29,14 +108,51 @@ fn switch(routes: Route) -> Html {
         Route::FileSystems => {
             html! { <FileSystems /> }
         }
+        Route::DiskUsage => {
+            html! { <DiskUsage /> }
+        }
+        Route::Network => {
+            html! { <Network /> }
+        }
+        Route::Ports => {
+            html! { <Ports /> }
+        }
+        Route::Overview => {
+            html! { <Overview /> }
+        }
+        Route::Alerts => {
+            html! { <Alerts /> }
+        }
+        Route::Leaks => {
+            html! { <Leaks /> }
+        }
+        Route::Settings => {
+            html! { <SettingsPage /> }
+        }
     }
 }
 
 #[function_component]
 pub fn App() -> Html {
+    // the pages only render once it is known which host they talk to
+    let loaded = use_state(|| false);
+    use_effect_with((), {
+        let loaded = loaded.clone();
+        move |_| {
+            spawn_local(async move {
+                hosts::load().await;
+                loaded.set(true);
+            });
+        }
+    });
+    if !*loaded {
+        return html! {};
+    }
+
     html! {
     <BrowserRouter>
             <Switch<Route> render={switch} />
+            <MountNotifications />
     </BrowserRouter>
 
     }
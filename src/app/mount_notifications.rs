@@ -0,0 +1,85 @@
+use super::events::use_tauri_event;
+use gloo_timers::callback::Timeout;
+use serde::Deserialize;
+use yew::prelude::*;
+
+const NOTIFICATION_TIMEOUT_MS: u32 = 6000;
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Mount {
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct MountChange {
+    pub added: Vec<Mount>,
+    pub removed: Vec<Mount>,
+}
+
+// toasts for drives that were mounted or unmounted while the app is open
+#[function_component(MountNotifications)]
+pub fn mount_notifications() -> Html {
+    let notifications = use_mut_ref(Vec::<(u32, String)>::new);
+    let next_id = use_mut_ref(|| 0u32);
+    let update = use_force_update();
+
+    let dismiss = {
+        let notifications = notifications.clone();
+        let update = update.clone();
+        Callback::from(move |id: u32| {
+            notifications.borrow_mut().retain(|(other, _)| *other != id);
+            update.force_update();
+        })
+    };
+
+    use_tauri_event("mounts-changed", {
+        let notifications = notifications.clone();
+        let dismiss = dismiss.clone();
+        Callback::from(move |change: MountChange| {
+            let messages =
+                change
+                    .added
+                    .iter()
+                    .map(|mount| {
+                        format!(
+                            "Mounted {} on {} ({})",
+                            mount.device, mount.mount_point, mount.file_system
+                        )
+                    })
+                    .chain(change.removed.iter().map(|mount| {
+                        format!("Unmounted {} from {}", mount.device, mount.mount_point)
+                    }));
+            for message in messages {
+                let id = {
+                    let mut next_id = next_id.borrow_mut();
+                    *next_id += 1;
+                    *next_id
+                };
+                notifications.borrow_mut().push((id, message));
+                let dismiss = dismiss.clone();
+                Timeout::new(NOTIFICATION_TIMEOUT_MS, move || dismiss.emit(id)).forget();
+            }
+            update.force_update();
+        })
+    });
+
+    html! {
+        <div class="fixed bottom-4 right-4 z-50 space-y-2">
+            {for notifications.borrow().iter().map(|(id, message)| {
+                let onclick = {
+                    let dismiss = dismiss.clone();
+                    let id = *id;
+                    Callback::from(move |_| dismiss.emit(id))
+                };
+                html! {
+                <div class="flex items-center w-full max-w-xs p-4 text-sm text-gray-500 bg-white rounded-lg shadow dark:text-gray-400 dark:bg-gray-800">
+                    <div class="me-3 font-normal">{message.clone()}</div>
+                    <button {onclick} type="button" class="ms-auto text-gray-400 hover:text-gray-900 rounded-lg p-1.5 dark:hover:text-white">{"✕"}</button>
+                </div>
+                }
+            })}
+        </div>
+    }
+}
@@ -0,0 +1,163 @@
+use super::{format_size, invoke};
+use crate::components::{navbar::NavBar, sparkline::Sparkline};
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use std::collections::{HashMap, VecDeque};
+use wasm_bindgen::JsValue;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+const HISTORY_LEN: usize = 60;
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct InterfaceInfo {
+    name: String,
+    mac: String,
+    addresses: Vec<String>,
+    mtu: u64,
+    is_virtual: bool,
+    received: u64,
+    transmitted: u64,
+    total_received: u64,
+    total_transmitted: u64,
+    total_packets_received: u64,
+    total_packets_transmitted: u64,
+    total_errors_on_received: u64,
+    total_errors_on_transmitted: u64,
+}
+
+#[derive(Default)]
+struct Throughput {
+    received: VecDeque<f64>,
+    transmitted: VecDeque<f64>,
+}
+
+fn push_sample(values: &mut VecDeque<f64>, value: u64) {
+    if values.len() == HISTORY_LEN {
+        values.pop_front();
+    }
+    values.push_back(value as f64);
+}
+
+#[function_component(Network)]
+pub fn network() -> Html {
+    let interfaces = use_state_eq(Vec::<InterfaceInfo>::new);
+    let history = use_mut_ref(HashMap::<String, Throughput>::new);
+    let exclude_virtual = use_state(|| true);
+
+    let get_interfaces = use_async({
+        let interfaces = interfaces.clone();
+        let history = history.clone();
+        async move {
+            let result = invoke("network_interfaces", JsValue::NULL).await;
+            if let Ok(ifaces) = from_value::<Vec<InterfaceInfo>>(result) {
+                let mut history = history.borrow_mut();
+                history.retain(|name, _| ifaces.iter().any(|iface| &iface.name == name));
+                for iface in &ifaces {
+                    let throughput = history.entry(iface.name.clone()).or_default();
+                    push_sample(&mut throughput.received, iface.received);
+                    push_sample(&mut throughput.transmitted, iface.transmitted);
+                }
+                interfaces.set(ifaces);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((), {
+        let get_interfaces = get_interfaces.clone();
+        move |_| get_interfaces.run()
+    });
+
+    {
+        let get_interfaces = get_interfaces.clone();
+        use_interval(
+            move || {
+                get_interfaces.run();
+            },
+            1000,
+        )
+    }
+
+    let on_toggle = Callback::from({
+        let exclude_virtual = exclude_virtual.clone();
+        move |_| exclude_virtual.set(!*exclude_virtual)
+    });
+
+    let counted = || {
+        interfaces
+            .iter()
+            .filter(|iface| !(*exclude_virtual && iface.is_virtual))
+    };
+    let received: u64 = counted().map(|iface| iface.received).sum();
+    let transmitted: u64 = counted().map(|iface| iface.transmitted).sum();
+    let total_received: u64 = counted().map(|iface| iface.total_received).sum();
+    let total_transmitted: u64 = counted().map(|iface| iface.total_transmitted).sum();
+
+    let history = history.borrow();
+
+    html! {
+    <>
+        <NavBar />
+        <div class="absolute sticky top-0 p-4 bg-white block sm:flex items-center justify-between border-b border-gray-200  dark:bg-gray-800 dark:border-gray-700">
+            <div class="flex space-x-6 text-sm font-medium text-gray-800 dark:text-gray-300">
+                <span>{format!("Received: {}/s", format_size(received))}</span>
+                <span>{format!("Transmitted: {}/s", format_size(transmitted))}</span>
+                <span>{format!("Total received: {}", format_size(total_received))}</span>
+                <span>{format!("Total transmitted: {}", format_size(total_transmitted))}</span>
+            </div>
+            <label class="inline-flex items-center cursor-pointer text-sm font-medium text-gray-800 dark:text-gray-300">
+                <input type="checkbox" checked={*exclude_virtual} onchange={on_toggle} class="w-4 h-4 me-2" />
+                {"Exclude loopback and virtual interfaces"}
+            </label>
+        </div>
+        <div class="flex flex-col">
+            <div class="overflow-x-auto">
+                <div class="inline-block min-w-full align-middle">
+                    <div class="overflow-y-auto shadow h-screen">
+                        <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+                            <thead class="bg-gray-100 dark:bg-gray-700 sticky top-0">
+                                <tr>
+                                    {for ["Interface", "Addresses", "MAC", "MTU", "Receive", "Transmit", "Packets (rx/tx)", "Errors (rx/tx)"].into_iter().map(|title| html!{
+                                    <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                        {title}
+                                    </th>
+                                    })}
+                                </tr>
+                            </thead>
+                            <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                                {for interfaces.iter().map(|iface| {
+                                    let (received, transmitted): (Vec<f64>, Vec<f64>) = history
+                                        .get(&iface.name)
+                                        .map(|t| (t.received.iter().cloned().collect(), t.transmitted.iter().cloned().collect()))
+                                        .unwrap_or_default();
+                                    html!{
+                                <tr class={format!("hover:bg-gray-100 dark:hover:bg-gray-700 {}", if iface.is_virtual { "opacity-60" } else { "" })}>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{iface.name.clone()}</td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                        {for iface.addresses.iter().map(|address| html!{ <div>{address.clone()}</div> })}
+                                    </td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{iface.mac.clone()}</td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{iface.mtu}</td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                        <Sparkline values={received} color="#16a34a" />
+                                        <div>{format!("{}/s", format_size(iface.received))}</div>
+                                    </td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                        <Sparkline values={transmitted} />
+                                        <div>{format!("{}/s", format_size(iface.transmitted))}</div>
+                                    </td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{} / {}", iface.total_packets_received, iface.total_packets_transmitted)}</td>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{} / {}", iface.total_errors_on_received, iface.total_errors_on_transmitted)}</td>
+                                </tr>
+                                    }
+                                })}
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        </div>
+    </>
+    }
+}
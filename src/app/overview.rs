@@ -0,0 +1,195 @@
+use super::hosts::open_on_host;
+use super::{format_size, tauri_invoke, Route};
+use crate::components::navbar::NavBar;
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::JsValue;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+// percentages from which a card is marked, the same as the terminal ui
+const WARNING: f64 = 80.0;
+const CRITICAL: f64 = 90.0;
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct MountUsage {
+    mount_point: String,
+    percent_used: f64,
+}
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct TopProcess {
+    pid: u32,
+    name: String,
+    cpu: f32,
+}
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct HostSummary {
+    hostname: String,
+    cpu: f32,
+    memory_used: u64,
+    memory_total: u64,
+    swap_used: u64,
+    swap_total: u64,
+    fullest_mount: Option<MountUsage>,
+    top_process: Option<TopProcess>,
+}
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct HostOverview {
+    id: Option<String>,
+    name: String,
+    url: String,
+    summary: Option<HostSummary>,
+    error: Option<String>,
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    }
+}
+
+fn level_class(percent: f64) -> &'static str {
+    if percent >= CRITICAL {
+        "text-red-600 dark:text-red-500"
+    } else if percent >= WARNING {
+        "text-yellow-600 dark:text-yellow-400"
+    } else {
+        ""
+    }
+}
+
+// the highest of cpu, memory, swap and the fullest mount decides the card's border
+fn worst(summary: &HostSummary) -> f64 {
+    [
+        summary.cpu as f64,
+        percent(summary.memory_used, summary.memory_total),
+        percent(summary.swap_used, summary.swap_total),
+        summary
+            .fullest_mount
+            .as_ref()
+            .map_or(0.0, |mount| mount.percent_used),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
+fn card_class(host: &HostOverview) -> String {
+    let border = match host.summary.as_ref().map(worst) {
+        None => "border-gray-400 opacity-60",
+        Some(worst) if worst >= CRITICAL => "border-red-500",
+        Some(worst) if worst >= WARNING => "border-yellow-400",
+        Some(_) => "border-gray-200 dark:border-gray-700",
+    };
+    format!(
+        "p-4 w-80 bg-white border-2 rounded-lg shadow text-sm text-gray-800 dark:bg-gray-800 dark:text-gray-300 {}",
+        border
+    )
+}
+
+fn row(label: &str, value: String, percent: f64) -> Html {
+    html! {
+        <div class="flex justify-between">
+            <span class="text-gray-500 dark:text-gray-400">{label.to_string()}</span>
+            <span class={level_class(percent)}>{value}</span>
+        </div>
+    }
+}
+
+// one card per host, opening a page from a card makes that host the active one
+#[function_component(Overview)]
+pub fn overview() -> Html {
+    let hosts = use_state_eq(Vec::<HostOverview>::new);
+
+    let get_overview = use_async({
+        let hosts = hosts.clone();
+        async move {
+            let result = tauri_invoke("host_overview", JsValue::NULL).await;
+            if let Ok(overview) = from_value::<Vec<HostOverview>>(result) {
+                hosts.set(overview);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((), {
+        let get_overview = get_overview.clone();
+        move |_| get_overview.run()
+    });
+
+    {
+        let get_overview = get_overview.clone();
+        use_interval(
+            move || {
+                get_overview.run();
+            },
+            2000,
+        )
+    }
+
+    let link_class = "px-3 py-1.5 text-white bg-blue-700 hover:bg-blue-800 rounded-lg dark:bg-blue-600 dark:hover:bg-blue-700";
+
+    html! {
+    <>
+        <NavBar />
+        <div class="p-4 flex flex-wrap gap-4">
+            {for hosts.iter().map(|host| {
+                let open = |route: Route| {
+                    let id = host.id.clone();
+                    Callback::from(move |_: MouseEvent| open_on_host(id.clone(), route.clone()))
+                };
+                html! {
+                    <div class={card_class(host)}>
+                        <div class="mb-2 flex items-baseline justify-between">
+                            <span class="text-base font-semibold">{host.name.clone()}</span>
+                            <span class="text-xs text-gray-500 dark:text-gray-400">
+                                {host.summary.as_ref().map(|summary| summary.hostname.clone()).unwrap_or_else(|| host.url.clone())}
+                            </span>
+                        </div>
+                        if let Some(summary) = &host.summary {
+                            {row("CPU", format!("{:.1}%", summary.cpu), summary.cpu as f64)}
+                            {row(
+                                "Memory",
+                                format!("{} / {}", format_size(summary.memory_used), format_size(summary.memory_total)),
+                                percent(summary.memory_used, summary.memory_total),
+                            )}
+                            {row(
+                                "Swap",
+                                format!("{} / {}", format_size(summary.swap_used), format_size(summary.swap_total)),
+                                percent(summary.swap_used, summary.swap_total),
+                            )}
+                            {match &summary.fullest_mount {
+                                Some(mount) => row(
+                                    "Fullest mount",
+                                    format!("{} {:.0}%", mount.mount_point, mount.percent_used),
+                                    mount.percent_used,
+                                ),
+                                None => row("Fullest mount", "-".into(), 0.0),
+                            }}
+                            {match &summary.top_process {
+                                Some(process) => row(
+                                    "Top process",
+                                    format!("{} ({}) {:.1}%", process.name, process.pid, process.cpu),
+                                    // above 100 on several cores, not a host threshold
+                                    0.0,
+                                ),
+                                None => row("Top process", "-".into(), 0.0),
+                            }}
+                            <div class="mt-3 flex space-x-2">
+                                <button onclick={open(Route::Processes)} class={link_class}>{"Processes"}</button>
+                                <button onclick={open(Route::FileSystems)} class={link_class}>{"File Systems"}</button>
+                            </div>
+                        } else {
+                            <span class="text-red-600 dark:text-red-500">{host.error.clone().unwrap_or_default()}</span>
+                        }
+                    </div>
+                }
+            })}
+        </div>
+    </>
+    }
+}
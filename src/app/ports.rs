@@ -0,0 +1,145 @@
+use super::invoke;
+use crate::components::navbar::NavBar;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+#[derive(Serialize)]
+struct Args {
+    id: usize,
+}
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct PortInfo {
+    protocol: String,
+    address: String,
+    port: u16,
+    pid: Option<u32>,
+    name: String,
+    user: String,
+}
+
+#[function_component(Ports)]
+pub fn ports() -> Html {
+    let ports = use_state_eq(Vec::<PortInfo>::new);
+    let port_filter = use_state(|| None::<String>);
+
+    let get_ports = use_async({
+        let ports = ports.clone();
+        async move {
+            let result = invoke("listening_ports", JsValue::NULL).await;
+            if let Ok(pr) = from_value::<Vec<PortInfo>>(result) {
+                ports.set(pr);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((), {
+        let get_ports = get_ports.clone();
+        move |_| get_ports.run()
+    });
+
+    {
+        let get_ports = get_ports.clone();
+        use_interval(
+            move || {
+                get_ports.run();
+            },
+            2000,
+        )
+    }
+
+    let search_input = use_node_ref();
+    let on_search = Callback::from({
+        let search_input = search_input.clone();
+        let port_filter = port_filter.clone();
+        move |e: SubmitEvent| {
+            e.prevent_default();
+            if let Some(input) = search_input.cast::<HtmlInputElement>() {
+                let value = input.value().trim().to_string();
+                port_filter.set((!value.is_empty()).then_some(value));
+            }
+        }
+    });
+
+    let kill_owner = |pid: u32| {
+        let get_ports = get_ports.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.stop_propagation();
+            let get_ports = get_ports.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                invoke(
+                    "kill_process",
+                    to_value(&Args { id: pid as usize }).unwrap(),
+                )
+                .await;
+                get_ports.run();
+            });
+        })
+    };
+
+    let visible: Vec<PortInfo> = (*ports)
+        .iter()
+        .filter(|port| match &*port_filter {
+            Some(filter) => port.port.to_string() == *filter || port.name.starts_with(filter),
+            None => true,
+        })
+        .cloned()
+        .collect();
+
+    html! {
+    <>
+        <NavBar />
+        <div class="absolute sticky top-0 p-4 bg-white block sm:flex items-center justify-between border-b border-gray-200  dark:bg-gray-800 dark:border-gray-700">
+            <form onsubmit={on_search} class="sm:pr-3" >
+                <label for="ports-search" class="sr-only">{"Search"}</label>
+                <div class="relative w-48 mt-1 sm:w-64 xl:w-96">
+                    <input ref={search_input} type="text" id="ports-search" class="bg-gray-50 border border-gray-300 text-gray-900 sm:text-sm rounded-lg focus:ring-blue-500 focus:border-blue-500 block w-full p-2.5 dark:bg-gray-700 dark:border-gray-600 dark:placeholder-gray-400 dark:text-white dark:focus:ring-blue-500 dark:focus:border-blue-500" placeholder="Who owns port..." />
+                </div>
+            </form>
+        </div>
+        <div class="flex flex-col">
+            <div class="overflow-x-auto">
+                <div class="inline-block min-w-full align-middle">
+                    <div class="overflow-y-auto shadow h-screen">
+                        <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+                            <thead class="bg-gray-100 dark:bg-gray-700 sticky top-0">
+                                <tr>
+                                    {for ["Protocol", "Address", "Port", "PID", "Process", "User", ""].into_iter().map(|title| html!{
+                                    <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                        {title}
+                                    </th>
+                                    })}
+                                </tr>
+                            </thead>
+                            <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                                {for visible.iter().map(|port| html!{
+                                <tr class="hover:bg-gray-100 dark:hover:bg-gray-700">
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{port.protocol.clone()}</td>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{port.address.clone()}</td>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{port.port}</td>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{port.pid.map(|pid| pid.to_string()).unwrap_or_else(|| "-".to_string())}</td>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{port.name.clone()}</td>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{port.user.clone()}</td>
+                                    <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">
+                                        if let Some(pid) = port.pid {
+                                            <button onclick={kill_owner(pid)} class="text-white bg-blue-700 hover:bg-blue-800 focus:ring-4 focus:ring-blue-300 font-medium rounded-lg text-xs px-3 py-1 dark:bg-blue-600 dark:hover:bg-blue-700 focus:outline-none dark:focus:ring-blue-800">
+                                                {"end process"}
+                                            </button>
+                                        }
+                                    </td>
+                                </tr>
+                                })}
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        </div>
+    </>
+    }
+}
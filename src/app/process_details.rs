@@ -0,0 +1,159 @@
+use super::hosts::is_remote;
+use super::invoke;
+use super::leaks::MemoryChart;
+use super::process_history::ProcessHistory;
+use super::processes::ProccessInfo;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+#[derive(Serialize)]
+struct Args {
+    pid: u32,
+}
+
+#[derive(Deserialize, PartialEq)]
+struct ThreadInfo {
+    id: u32,
+    name: String,
+    state: String,
+    cpu: f32,
+    last_cpu: i32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Overview,
+    History,
+    Threads,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ProcessDetailsProps {
+    pub process: ProccessInfo,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(ProcessDetails)]
+pub fn process_details(props: &ProcessDetailsProps) -> Html {
+    let tab = use_state(|| Tab::Overview);
+    let threads = use_state_eq(Vec::<ThreadInfo>::new);
+    let pid = props.process.id.parse::<u32>().unwrap_or(0);
+
+    let get_threads = use_async({
+        let threads = threads.clone();
+        async move {
+            let result = invoke("process_threads", to_value(&Args { pid }).unwrap()).await;
+            if let Ok(th) = from_value::<Vec<ThreadInfo>>(result) {
+                threads.set(th);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((pid, *tab), {
+        let get_threads = get_threads.clone();
+        move |(_, tab)| {
+            if *tab == Tab::Threads {
+                get_threads.run();
+            }
+        }
+    });
+
+    {
+        let get_threads = get_threads.clone();
+        let tab = *tab;
+        use_interval(
+            move || {
+                if tab == Tab::Threads {
+                    get_threads.run();
+                }
+            },
+            1000,
+        )
+    }
+
+    let on_close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| on_close.emit(()))
+    };
+
+    let active_class = "cursor-pointer inline-block p-2 text-blue-600 border-b-2 border-blue-600 rounded-t-lg active dark:text-blue-500 dark:border-blue-500";
+    let passive_class = "cursor-pointer inline-block p-2 border-b-2 border-transparent rounded-t-lg hover:text-gray-600 hover:border-gray-300 dark:hover:text-gray-300";
+    let tab_link = |target: Tab, label: &'static str| {
+        let onclick = {
+            let tab = tab.clone();
+            Callback::from(move |_| tab.set(target))
+        };
+        html! {
+            <li class="me-1">
+                <a {onclick} class={if *tab == target { active_class } else { passive_class }}>{label}</a>
+            </li>
+        }
+    };
+
+    let process = &props.process;
+    let row = |label: &'static str, value: String| {
+        html! {
+            <tr>
+                <td class="px-2 py-1 text-sm font-medium text-gray-500 whitespace-nowrap dark:text-gray-400">{label}</td>
+                <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{value}</td>
+            </tr>
+        }
+    };
+
+    html! {
+    <div class="fixed top-0 right-0 z-50 h-screen w-1/3 overflow-y-auto bg-white border-l border-gray-200 shadow dark:bg-gray-800 dark:border-gray-700">
+        <div class="flex items-center justify-between p-4">
+            <h5 class="text-base font-semibold text-gray-500 uppercase dark:text-gray-400">{format!("{} ({})", process.name, process.id)}</h5>
+            <button onclick={on_close} type="button" class="text-gray-400 bg-transparent hover:bg-gray-200 hover:text-gray-900 rounded-lg text-sm p-1.5 dark:hover:bg-gray-600 dark:hover:text-white">{"✕"}</button>
+        </div>
+        <ul class="flex flex-wrap px-4 -mb-px text-sm font-medium text-center text-gray-500 border-b border-gray-200 dark:text-gray-400 dark:border-gray-700">
+            {tab_link(Tab::Overview, "Overview")}
+            {tab_link(Tab::History, "History")}
+            {tab_link(Tab::Threads, "Threads")}
+        </ul>
+        if *tab == Tab::Overview {
+            <table class="min-w-full m-2">
+                <tbody>
+                    {row("CPU", format!("{} %", process.cpu))}
+                    {row("Memory", format!("{} MB", process.memory))}
+                    {row("Disk Read", format!("{} MB", process.disk_read))}
+                    {row("Disk Write", format!("{} MB", process.disk_write))}
+                    {row("Threads", process.threads.to_string())}
+                </tbody>
+            </table>
+            // recorded by this machine's history only
+            if !is_remote() {
+                <MemoryChart {pid} />
+            }
+        } else if *tab == Tab::History {
+            <ProcessHistory {pid} start_time={process.start_time} />
+        } else {
+            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+                <thead class="bg-gray-100 dark:bg-gray-700 sticky top-0">
+                    <tr>
+                        {for ["TID", "Name", "State", "CPU", "Last CPU"].into_iter().map(|title| html!{
+                        <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                            {title}
+                        </th>
+                        })}
+                    </tr>
+                </thead>
+                <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
+                    {for (*threads).iter().map(|thread| html!{
+                    <tr class="hover:bg-gray-100 dark:hover:bg-gray-700">
+                        <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{thread.id}</td>
+                        <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{thread.name.clone()}</td>
+                        <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{thread.state.clone()}</td>
+                        <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{format!("{:.1} %", thread.cpu)}</td>
+                        <td class="px-2 py-1 text-sm font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{thread.last_cpu}</td>
+                    </tr>
+                    })}
+                </tbody>
+            </table>
+        }
+    </div>
+    }
+}
@@ -0,0 +1,313 @@
+use super::time_travel::format_timestamp;
+use super::{format_size, invoke};
+use plotters::prelude::*;
+use plotters_canvas::CanvasBackend;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+use yew::prelude::*;
+use yew_hooks::{use_async, use_interval};
+
+const WIDTH: u32 = 440;
+const HEIGHT: u32 = 440;
+// the plot areas of all charts start and end at the same x, the hover and zoom rely on it
+const MARGIN: i32 = 8;
+const Y_LABELS: i32 = 64;
+const X_LABELS: i32 = 20;
+// zooming in stops at this many milliseconds
+const MIN_SPAN: u64 = 10 * 1000;
+const ZOOM_STEP: f64 = 1.25;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Args {
+    pid: u32,
+    start_time: u64,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+struct ProcessPoint {
+    timestamp: u64,
+    cpu: f32,
+    memory: u64,
+    disk_read: u64,
+    disk_write: u64,
+    threads: usize,
+}
+
+type Value = fn(&ProcessPoint) -> f64;
+
+// one chart per unit, disk read and write share theirs
+struct Chart {
+    title: &'static str,
+    lines: &'static [(Value, RGBColor)],
+    format: fn(f64) -> String,
+}
+
+const READ: RGBColor = RGBColor(22, 163, 74);
+const WRITE: RGBColor = RGBColor(37, 99, 235);
+
+const CHARTS: [Chart; 4] = [
+    Chart {
+        title: "CPU",
+        lines: &[(|point| point.cpu as f64, RGBColor(220, 38, 38))],
+        format: |value| format!("{:.0} %", value),
+    },
+    Chart {
+        title: "Memory",
+        lines: &[(|point| point.memory as f64, RGBColor(147, 51, 234))],
+        format: |value| format_size(value as u64),
+    },
+    Chart {
+        title: "Disk",
+        lines: &[
+            (|point| point.disk_read as f64, READ),
+            (|point| point.disk_write as f64, WRITE),
+        ],
+        format: |value| format!("{}/s", format_size(value as u64)),
+    },
+    Chart {
+        title: "Threads",
+        lines: &[(|point| point.threads as f64, RGBColor(234, 88, 12))],
+        format: |value| format!("{:.0}", value),
+    },
+];
+
+fn format_clock(timestamp: u64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(timestamp as f64))
+        .to_locale_time_string("default")
+        .into()
+}
+
+fn plot_left() -> i32 {
+    MARGIN + Y_LABELS
+}
+
+fn plot_width() -> i32 {
+    WIDTH as i32 - 2 * MARGIN - Y_LABELS
+}
+
+// the time under a canvas x, clamped to the view
+fn time_at(x: i32, view: (u64, u64)) -> u64 {
+    let ratio = ((x - plot_left()) as f64 / plot_width() as f64).clamp(0.0, 1.0);
+    view.0 + ((view.1 - view.0) as f64 * ratio) as u64
+}
+
+fn x_of(timestamp: u64, view: (u64, u64)) -> i32 {
+    let span = (view.1 - view.0).max(1) as f64;
+    plot_left() + (timestamp.saturating_sub(view.0) as f64 / span * plot_width() as f64) as i32
+}
+
+fn draw(
+    canvas: HtmlCanvasElement,
+    points: &[ProcessPoint],
+    view: (u64, u64),
+    hover: Option<u64>,
+) -> Option<()> {
+    let root = CanvasBackend::with_canvas_object(canvas)?.into_drawing_area();
+    root.fill(&WHITE).ok()?;
+    let visible: Vec<&ProcessPoint> = points
+        .iter()
+        .filter(|point| point.timestamp >= view.0 && point.timestamp <= view.1)
+        .collect();
+    let areas = root.split_evenly((CHARTS.len(), 1));
+    for (i, (area, chart)) in areas.iter().zip(CHARTS.iter()).enumerate() {
+        let max = visible
+            .iter()
+            .flat_map(|point| chart.lines.iter().map(move |(value, _)| value(point)))
+            .fold(0.0, f64::max)
+            .max(1.0)
+            * 1.1;
+        let last = i + 1 == CHARTS.len();
+        let mut plot = ChartBuilder::on(area)
+            .margin(MARGIN)
+            .y_label_area_size(Y_LABELS)
+            .x_label_area_size(if last { X_LABELS } else { 0 })
+            .build_cartesian_2d(view.0..view.1.max(view.0 + 1), 0.0..max)
+            .ok()?;
+        plot.configure_mesh()
+            .disable_x_mesh()
+            .x_labels(4)
+            .x_label_formatter(&|timestamp| format_clock(*timestamp))
+            .y_labels(3)
+            .y_label_formatter(&|value| (chart.format)(*value))
+            .y_desc(chart.title)
+            .draw()
+            .ok()?;
+        for (value, color) in chart.lines {
+            plot.draw_series(LineSeries::new(
+                visible.iter().map(|point| (point.timestamp, value(point))),
+                color,
+            ))
+            .ok()?;
+        }
+        if let Some(at) = hover {
+            plot.draw_series(std::iter::once(PathElement::new(
+                vec![(at, 0.0), (at, max)],
+                BLACK.mix(0.3),
+            )))
+            .ok()?;
+        }
+    }
+    root.present().ok()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ProcessHistoryProps {
+    pub pid: u32,
+    pub start_time: u64,
+}
+
+// what the process did since the app first saw it, the wheel zooms into time,
+// a double click shows everything again
+#[function_component(ProcessHistory)]
+pub fn process_history(props: &ProcessHistoryProps) -> Html {
+    let points = use_state_eq(Vec::<ProcessPoint>::new);
+    // None follows the whole lifetime as it grows
+    let zoom = use_state_eq(|| None::<(u64, u64)>);
+    let hover = use_state_eq(|| None::<(i32, i32, ProcessPoint)>);
+    let canvas = use_node_ref();
+    let (pid, start_time) = (props.pid, props.start_time);
+
+    let get_history = use_async({
+        let points = points.clone();
+        async move {
+            let args = to_value(&Args { pid, start_time }).unwrap();
+            if let Ok(history) =
+                from_value::<Vec<ProcessPoint>>(invoke("process_history", args).await)
+            {
+                points.set(history);
+            }
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with((pid, start_time), {
+        let get_history = get_history.clone();
+        let zoom = zoom.clone();
+        move |_| {
+            zoom.set(None);
+            get_history.run();
+        }
+    });
+
+    {
+        let get_history = get_history.clone();
+        use_interval(
+            move || {
+                get_history.run();
+            },
+            2000,
+        )
+    }
+
+    let full = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (first.timestamp, last.timestamp.max(first.timestamp + 1)),
+        _ => (0, 1),
+    };
+    let view = zoom.unwrap_or(full);
+
+    use_effect_with(
+        (
+            (*points).clone(),
+            view,
+            hover.as_ref().map(|(_, _, point)| point.timestamp),
+        ),
+        {
+            let canvas = canvas.clone();
+            move |(points, view, hover): &(Vec<ProcessPoint>, (u64, u64), Option<u64>)| {
+                if let Some(canvas) = canvas.cast::<HtmlCanvasElement>() {
+                    draw(canvas, points, *view, *hover);
+                }
+            }
+        },
+    );
+
+    let on_wheel = {
+        let zoom = zoom.clone();
+        Callback::from(move |e: WheelEvent| {
+            e.prevent_default();
+            let at = time_at(e.offset_x(), view);
+            let factor = if e.delta_y() < 0.0 {
+                1.0 / ZOOM_STEP
+            } else {
+                ZOOM_STEP
+            };
+            let span = (((view.1 - view.0) as f64 * factor) as u64).max(MIN_SPAN);
+            if span >= full.1 - full.0 {
+                zoom.set(None);
+                return;
+            }
+            // the time under the cursor stays where it is
+            let before = ((at - view.0) as f64 * span as f64 / (view.1 - view.0) as f64) as u64;
+            let start = at.saturating_sub(before).clamp(full.0, full.1 - span);
+            zoom.set(Some((start, start + span)));
+        })
+    };
+
+    let on_move = {
+        let hover = hover.clone();
+        let points = points.clone();
+        Callback::from(move |e: MouseEvent| {
+            let at = time_at(e.offset_x(), view);
+            let nearest = points
+                .iter()
+                .filter(|point| point.timestamp >= view.0 && point.timestamp <= view.1)
+                .min_by_key(|point| point.timestamp.abs_diff(at))
+                .copied();
+            hover.set(nearest.map(|point| (x_of(point.timestamp, view), e.offset_y(), point)));
+        })
+    };
+
+    let on_leave = {
+        let hover = hover.clone();
+        Callback::from(move |_: MouseEvent| hover.set(None))
+    };
+
+    let on_reset = {
+        let zoom = zoom.clone();
+        Callback::from(move |_: MouseEvent| zoom.set(None))
+    };
+
+    html! {
+        <div class="px-4 py-2 text-sm text-gray-800 dark:text-gray-300">
+            if points.len() < 2 {
+                <span class="text-gray-500 dark:text-gray-400">{"Not observed long enough."}</span>
+            }
+            <div class="relative" style={format!("width: {}px", WIDTH)}>
+                <canvas
+                    ref={canvas}
+                    width={WIDTH.to_string()}
+                    height={HEIGHT.to_string()}
+                    class="rounded-lg"
+                    onwheel={on_wheel}
+                    onmousemove={on_move}
+                    onmouseleave={on_leave}
+                    ondblclick={on_reset}
+                />
+                if let Some((x, y, point)) = *hover {
+                    <div
+                        class="absolute pointer-events-none p-2 text-xs bg-white border border-gray-200 rounded-lg shadow whitespace-nowrap dark:bg-gray-700 dark:border-gray-600"
+                        style={format!("left: {}px; top: {}px", (x + 12).min(WIDTH as i32 - 180), y + 12)}
+                    >
+                        <div class="font-semibold">{format_timestamp(point.timestamp)}</div>
+                        {for CHARTS.iter().flat_map(|chart| {
+                            chart.lines.iter().enumerate().map(move |(i, (value, _))| {
+                                let label = match (chart.title, i) {
+                                    ("Disk", 0) => "Read",
+                                    ("Disk", _) => "Write",
+                                    (title, _) => title,
+                                };
+                                html! { <div>{format!("{}: {}", label, (chart.format)(value(&point)))}</div> }
+                            })
+                        })}
+                    </div>
+                }
+            </div>
+            if zoom.is_some() {
+                <span class="text-xs text-gray-500 dark:text-gray-400">{"Double click to show the whole lifetime."}</span>
+            }
+        </div>
+    }
+}
@@ -1,8 +1,14 @@
+use super::columns::{Column, ColumnChooser, DEFAULT_COLUMNS};
+use super::export::{self, Export, ExportSource};
 use super::invoke;
+use super::process_details::ProcessDetails;
+use super::settings::{self, format_interval, Patch, Sort, REFRESH_INTERVALS};
+use super::time_travel::{TimeSelection, TimeTravel};
 use crate::components::navbar::NavBar;
 use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::{from_value, to_value};
-use web_sys::HtmlInputElement;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 use yew_hooks::{use_async, use_interval};
 
@@ -12,21 +18,104 @@ struct Args {
 }
 
 mod get_args {
+    use crate::app::columns::Column;
     use serde::Serialize;
     #[derive(Serialize)]
     pub struct Args {
         pub name: Option<String>,
+        // the backend only works out the fields of these
+        pub columns: Vec<Column>,
     }
 }
 
-#[derive(Serialize, Deserialize, PartialEq)]
-struct ProccessInfo {
-    id: String,
+// exported history without a diff range covers the hour before the selected time
+const HISTORY_EXPORT_MS: u64 = 60 * 60 * 1000;
+
+#[derive(Serialize)]
+struct SnapshotArgs {
+    timestamp: u64,
+}
+
+#[derive(Serialize)]
+struct DiffArgs {
+    range: export::TimeRange,
+}
+
+#[derive(Deserialize)]
+struct RecordedProcess {
+    pid: u32,
     name: String,
     cpu: f32,
     memory: u64,
-    disk_read: u64,
-    disk_write: u64,
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    processes: Vec<RecordedProcess>,
+}
+
+#[derive(Deserialize, PartialEq, Clone)]
+struct ProcessRef {
+    pid: u32,
+    name: String,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Default)]
+struct ProcessDiff {
+    started: Vec<ProcessRef>,
+    ended: Vec<ProcessRef>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default)]
+pub(super) struct ProccessInfo {
+    pub id: String,
+    pub name: String,
+    pub cpu: f32,
+    pub memory: u64,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    pub threads: usize,
+    #[serde(default)]
+    pub start_time: u64,
+    // the last samples the backend kept, oldest first
+    #[serde(default)]
+    pub cpu_trend: Vec<f32>,
+    #[serde(default)]
+    pub memory_trend: Vec<u64>,
+    // only there when their column is shown
+    #[serde(default)]
+    pub ppid: Option<u32>,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub nice: Option<i32>,
+    #[serde(default)]
+    pub run_time: Option<u64>,
+    #[serde(default)]
+    pub command_line: Option<String>,
+    #[serde(default)]
+    pub exe: Option<String>,
+    #[serde(default)]
+    pub cpu_time: Option<u64>,
+    #[serde(default)]
+    pub virtual_memory: Option<u64>,
+    #[serde(default)]
+    pub read_rate: Option<u64>,
+    #[serde(default)]
+    pub write_rate: Option<u64>,
+    #[serde(default)]
+    pub open_files: Option<usize>,
+    #[serde(default)]
+    pub cgroup: Option<String>,
+}
+
+// saved in the background, the table already shows the change
+fn save(patch: Patch) {
+    spawn_local(async move {
+        let _ = settings::update(patch).await;
+    });
 }
 
 #[function_component(Processes)]
@@ -34,6 +123,15 @@ pub fn processes() -> Html {
     let processes = use_state_eq(|| Vec::<ProccessInfo>::new());
     let process_id = use_state(|| None::<String>);
     let search_filter = use_state(|| None::<String>);
+    let selection = use_state(TimeSelection::default);
+    let diff = use_state_eq(|| None::<ProcessDiff>);
+    let columns = use_state_eq(|| DEFAULT_COLUMNS.to_vec());
+    let sort = use_state_eq(Sort::default);
+    let refresh_interval = use_state_eq(|| 1000u32);
+    let paused = use_state_eq(|| false);
+    let freeze_on_hover = use_state_eq(|| false);
+    let hovering = use_state_eq(|| false);
+    let live = selection.at.is_none();
 
     let kill_process = use_async({
         let process_id = process_id.clone();
@@ -62,27 +160,193 @@ pub fn processes() -> Html {
     let get_process = use_async({
         let processes = processes.clone();
         let search_filter = search_filter.clone();
+        let columns = columns.clone();
+        let at = selection.at;
         async move {
+            let Some(timestamp) = at else {
+                let result = invoke(
+                    "process_info",
+                    to_value(&get_args::Args {
+                        name: (*search_filter).clone(),
+                        columns: (*columns).clone(),
+                    })
+                    .unwrap(),
+                )
+                .await;
+                if let Ok(pr) = from_value::<Vec<ProccessInfo>>(result) {
+                    processes.set(pr);
+                }
+                return Ok(());
+            };
             let result = invoke(
-                "process_info",
-                to_value(&get_args::Args {
-                    name: (*search_filter).clone(),
-                })
-                .unwrap(),
+                "history_snapshot",
+                to_value(&SnapshotArgs { timestamp }).unwrap(),
             )
             .await;
-            if let Ok(pr) = from_value::<Vec<ProccessInfo>>(result) {
-                processes.set(pr);
+            if let Ok(snapshot) = from_value::<Option<Snapshot>>(result) {
+                let mut recorded: Vec<ProccessInfo> = snapshot
+                    .map(|snapshot| snapshot.processes)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|process| match &*search_filter {
+                        Some(name) => process.name.starts_with(name.as_str()),
+                        None => true,
+                    })
+                    .map(|process| ProccessInfo {
+                        id: process.pid.to_string(),
+                        name: process.name,
+                        cpu: process.cpu,
+                        memory: process.memory / 1024 / 1024,
+                        ..Default::default()
+                    })
+                    .collect();
+                recorded.sort_by_key(|process| process.name.clone());
+                processes.set(recorded);
             }
             Ok::<(), ()>(())
         }
     });
 
-    use_effect_with((), {
+    let get_diff = use_async({
+        let diff = diff.clone();
+        let selection = *selection;
+        async move {
+            let (Some(at), Some(from)) = (selection.at, selection.diff_from) else {
+                diff.set(None);
+                return Ok(());
+            };
+            let range = export::TimeRange {
+                start: from.min(at),
+                end: from.max(at),
+            };
+            let result = invoke("history_diff", to_value(&DiffArgs { range }).unwrap()).await;
+            diff.set(from_value::<ProcessDiff>(result).ok());
+            Ok::<(), ()>(())
+        }
+    });
+
+    use_effect_with(*selection, {
+        let get_process = get_process.clone();
+        let get_diff = get_diff.clone();
+        move |_| {
+            get_process.run();
+            get_diff.run();
+        }
+    });
+
+    // a new filter or other columns show without waiting for the next refresh
+    use_effect_with(((*search_filter).clone(), (*columns).clone()), {
         let get_process = get_process.clone();
         move |_| get_process.run()
     });
 
+    let on_selection = Callback::from({
+        let selection = selection.clone();
+        let process_id = process_id.clone();
+        move |value: TimeSelection| {
+            process_id.set(None);
+            selection.set(value);
+        }
+    });
+
+    // the table starts the way it was left
+    use_effect_with((), {
+        let columns = columns.clone();
+        let sort = sort.clone();
+        let search_filter = search_filter.clone();
+        let refresh_interval = refresh_interval.clone();
+        let freeze_on_hover = freeze_on_hover.clone();
+        move |_| {
+            spawn_local(async move {
+                if let Some(saved) = settings::load().await {
+                    columns.set(saved.columns);
+                    sort.set(saved.sort);
+                    search_filter.set(saved.filter);
+                    refresh_interval.set(saved.refresh_interval);
+                    freeze_on_hover.set(saved.freeze_on_hover);
+                }
+            });
+        }
+    });
+
+    let on_columns = Callback::from({
+        let columns = columns.clone();
+        move |next: Vec<Column>| {
+            columns.set(next.clone());
+            save(Patch {
+                columns: Some(next),
+                ..Patch::default()
+            });
+        }
+    });
+
+    let on_sort = |column: Column| {
+        let sort = sort.clone();
+        Callback::from(move |_: MouseEvent| {
+            // a second click on the same column turns the order around
+            let next = Sort {
+                column,
+                descending: sort.column == column && !sort.descending,
+            };
+            sort.set(next);
+            save(Patch {
+                sort: Some(next),
+                ..Patch::default()
+            });
+        })
+    };
+
+    // the backend measures the cpu column between polls, so it covers the whole interval
+    let on_interval = Callback::from({
+        let refresh_interval = refresh_interval.clone();
+        move |e: Event| {
+            let Ok(millis) = e
+                .target_unchecked_into::<HtmlSelectElement>()
+                .value()
+                .parse::<u32>()
+            else {
+                return;
+            };
+            refresh_interval.set(millis);
+            save(Patch {
+                refresh_interval: Some(millis),
+                ..Patch::default()
+            });
+        }
+    });
+
+    let on_pause = Callback::from({
+        let paused = paused.clone();
+        let get_process = get_process.clone();
+        move |_: MouseEvent| {
+            if *paused {
+                get_process.run();
+            }
+            paused.set(!*paused);
+        }
+    });
+
+    let on_freeze = Callback::from({
+        let freeze_on_hover = freeze_on_hover.clone();
+        move |e: Event| {
+            let freeze = e.target_unchecked_into::<HtmlInputElement>().checked();
+            freeze_on_hover.set(freeze);
+            save(Patch {
+                freeze_on_hover: Some(freeze),
+                ..Patch::default()
+            });
+        }
+    });
+
+    let on_enter_table = Callback::from({
+        let hovering = hovering.clone();
+        move |_: MouseEvent| hovering.set(true)
+    });
+    let on_leave_table = Callback::from({
+        let hovering = hovering.clone();
+        move |_: MouseEvent| hovering.set(false)
+    });
+
     let search_input = use_node_ref();
 
     let on_search = Callback::from({
@@ -91,20 +355,99 @@ pub fn processes() -> Html {
         move |e: SubmitEvent| {
             e.prevent_default();
             if let Some(input) = search_input.cast::<HtmlInputElement>() {
-                search_filter.set(Some(input.value()));
+                let filter = input.value();
+                search_filter.set(Some(filter.clone()));
+                save(Patch {
+                    filter: Some(Some(filter)),
+                    ..Patch::default()
+                });
             }
         }
     });
 
+    let on_close_details = Callback::from({
+        let process_id = process_id.clone();
+        move |_| process_id.set(None)
+    });
+    let selected = (*process_id)
+        .as_ref()
+        .filter(|_| live)
+        .and_then(|id| processes.iter().find(|process| &process.id == id))
+        .cloned();
+
+    // rows that keep moving cannot be clicked
+    let held = *freeze_on_hover && (*hovering || process_id.is_some());
+    let frozen = *paused || held;
     {
         let get_process = get_process.clone();
         use_interval(
             move || {
-                get_process.run();
+                if live && !frozen {
+                    get_process.run();
+                }
             },
-            1000,
+            *refresh_interval,
         )
     }
+    // ties keep the backend's order by name
+    let mut rows = (*processes).clone();
+    rows.sort_by(|a, b| {
+        if sort.descending {
+            sort.column.compare(b, a)
+        } else {
+            sort.column.compare(a, b)
+        }
+    });
+
+    // started processes are highlighted in the table, ended ones and started ones
+    // the table does not have are listed after it
+    let started: Vec<String> = (*diff)
+        .iter()
+        .flat_map(|diff| diff.started.iter().map(|process| process.pid.to_string()))
+        .collect();
+    let listed: Vec<(ProcessRef, bool)> = (*diff)
+        .iter()
+        .flat_map(|diff| {
+            let missing = diff
+                .started
+                .iter()
+                .filter(|process| !rows.iter().any(|row| row.id == process.pid.to_string()))
+                .map(|process| (process.clone(), false));
+            missing.chain(diff.ended.iter().map(|process| (process.clone(), true)))
+        })
+        .filter(|(process, _)| match &*search_filter {
+            Some(name) => process.name.starts_with(name.as_str()),
+            None => true,
+        })
+        .collect();
+    // said once, in the first column that is not the name or pid
+    let label_column = columns
+        .iter()
+        .position(|column| !matches!(column, Column::Name | Column::Pid));
+
+    // in history mode the recorded metrics around the selected time can be exported too
+    let mut export_sources = vec![(
+        AttrValue::from("processes"),
+        ExportSource::Processes {
+            name: (*search_filter).clone(),
+        },
+    )];
+    if let Some(at) = selection.at {
+        let from = selection
+            .diff_from
+            .unwrap_or(at.saturating_sub(HISTORY_EXPORT_MS));
+        export_sources.push((
+            AttrValue::from("history"),
+            ExportSource::History {
+                range: export::TimeRange {
+                    start: from.min(at),
+                    end: from.max(at),
+                },
+                resolution: 0,
+            },
+        ));
+    }
+
     html! {
 
     <>
@@ -118,16 +461,33 @@ pub fn processes() -> Html {
                                                  <form onsubmit={on_search} class="sm:pr-3" >
                                                      <label for="products-search" class="sr-only">{"Search"}</label>
                                                      <div class="relative w-48 mt-1 sm:w-64 xl:w-96">
-                                                         <input ref={search_input} type="text" name="email" id="products-search" class="bg-gray-50 border border-gray-300 text-gray-900 sm:text-sm rounded-lg focus:ring-blue-500 focus:border-blue-500 block w-full p-2.5 dark:bg-gray-700 dark:border-gray-600 dark:placeholder-gray-400 dark:text-white dark:focus:ring-blue-500 dark:focus:border-blue-500" placeholder="Search by process name" />
+                                                         <input ref={search_input} type="text" name="email" id="products-search" class="bg-gray-50 border border-gray-300 text-gray-900 sm:text-sm rounded-lg focus:ring-blue-500 focus:border-blue-500 block w-full p-2.5 dark:bg-gray-700 dark:border-gray-600 dark:placeholder-gray-400 dark:text-white dark:focus:ring-blue-500 dark:focus:border-blue-500" placeholder="Search by process name" value={(*search_filter).clone().unwrap_or_default()} />
                                                      </div>
                                                  </form>
                                                  <div class="flex items-center w-full sm:justify-end">
                                                      <div class="flex pl-2 space-x-1">
+                                                         <TimeTravel selection={*selection} on_change={on_selection} />
+                                                         <Export sources={export_sources} />
+                                                         <ColumnChooser columns={(*columns).clone()} on_change={on_columns} />
+                                                         if live {
+                                                             <select onchange={on_interval} title="Refresh every" class="text-gray-900 bg-white border border-gray-300 font-medium rounded-lg text-sm px-2 py-2.5 dark:bg-gray-800 dark:text-white dark:border-gray-600">
+                                                                 {for REFRESH_INTERVALS.iter().map(|millis| html!{
+                                                                     <option value={millis.to_string()} selected={*millis == *refresh_interval}>{format_interval(*millis)}</option>
+                                                                 })}
+                                                             </select>
+                                                             <button type="button" onclick={on_pause} class="text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-sm px-4 py-2.5 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700">
+                                                                 {if *paused { "Resume" } else { "Pause" }}
+                                                             </button>
+                                                             <label class="inline-flex items-center px-2 text-sm font-medium text-gray-900 whitespace-nowrap dark:text-white" title="Hold the table still under the pointer or with a process selected">
+                                                                 <input type="checkbox" checked={*freeze_on_hover} onchange={on_freeze} class="w-4 h-4 me-2" />
+                                                                 {if held { "Frozen" } else { "Freeze on hover" }}
+                                                             </label>
+                                                         }
                                                      </div>
                                                  </div>
                                              </div>
                                             <form onsubmit={on_kill_process}>
-                                             <button disabled={process_id.is_none()} type="submit" id="createProductButton" class={format!("text-white bg-blue-700 hover:bg-blue-800 focus:ring-4 focus:ring-blue-300 font-medium rounded-lg text-sm px-5 py-2.5 dark:bg-blue-600 dark:hover:bg-blue-700 focus:outline-none dark:focus:ring-blue-800 {}", if process_id.is_some(){""}else{"opacity-30"})} data-drawer-target="drawer-create-product-default" data-drawer-show="drawer-create-product-default" aria-controls="drawer-create-product-default" data-drawer-placement="right">
+                                             <button disabled={process_id.is_none() || !live} type="submit" id="createProductButton" class={format!("text-white bg-blue-700 hover:bg-blue-800 focus:ring-4 focus:ring-blue-300 font-medium rounded-lg text-sm px-5 py-2.5 dark:bg-blue-600 dark:hover:bg-blue-700 focus:outline-none dark:focus:ring-blue-800 {}", if process_id.is_some() && live {""}else{"opacity-30"})} data-drawer-target="drawer-create-product-default" data-drawer-show="drawer-create-product-default" aria-controls="drawer-create-product-default" data-drawer-placement="right">
                                          { "end process" }
                                              </button>
                                             </form>
@@ -138,31 +498,21 @@ pub fn processes() -> Html {
                                  <div class="overflow-x-auto">
                                      <div class="inline-block min-w-full align-middle">
                                          <div class="overflow-y-auto shadow h-screen">
-                                             <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
+                                             <table onmouseenter={on_enter_table} onmouseleave={on_leave_table} class="min-w-full divide-y divide-gray-200 dark:divide-gray-600">
                                                  <thead class="bg-gray-100 dark:bg-gray-700 sticky top-0">
                                                      <tr>
-                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
-                                     {"Name"}
-                                                         </th>
-                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
-                                     {"ID"}
-                                                         </th>
-                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
-                                     {"CPU"}
-                                                         </th>
-                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
-                                     {"Memory"}
-                                                         </th>
-                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
-                                     {"Disk Read"}
-                                                         </th>
-                                                         <th scope="col" class="py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
-                                     {"Disk Write"}
+                                                         {for columns.iter().map(|column| html!{
+                                                         <th scope="col" onclick={on_sort(*column)} class="cursor-pointer py-1 px-2 text-xs font-medium text-left text-gray-500 uppercase dark:text-gray-400">
+                                     {column.label()}
+                                     if sort.column == *column {
+                                         {if sort.descending { " ▼" } else { " ▲" }}
+                                     }
                                                          </th>
+                                                         })}
                                                      </tr>
                                                  </thead>
                                                  <tbody class="bg-white divide-y divide-gray-200 dark:bg-gray-800 dark:divide-gray-700">
-                                                     {for (*processes).iter().map(|process| html!{
+                                                     {for rows.iter().map(|process| html!{
                                                  <tr
                                                     onclick={
                                                          Callback::from({
@@ -171,14 +521,27 @@ pub fn processes() -> Html {
                                                             move |_| process_id.set(Some(id.clone()))
                                                          })
                                                      }
-                                                         class={format!("cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 {}",
-                                        if let Some(proc_id) = (*process_id).clone() { if proc_id == process.id { "bg-gray-100 dark:bg-gray-700" }else{ "" }}else{""})}>
-                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.name.clone()}</td>
-                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.id.clone()}</td>
-                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.cpu.clone()}{" %"}</td>
-                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.memory.clone()}{" MB"}</td>
-                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.disk_read.clone()}{" MB"}</td>
-                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.disk_write.clone()}{" MB"}</td>
+                                                         class={format!("cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-700 {} {}",
+                                        if let Some(proc_id) = (*process_id).clone() { if proc_id == process.id { "bg-gray-100 dark:bg-gray-700" }else{ "" }}else{""},
+                                        if started.contains(&process.id) { "bg-green-100 dark:bg-green-900" } else { "" })}>
+                                                     {for columns.iter().map(|column| html!{
+                                                     <td class="cursor-pointer px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{column.cell(process, live)}</td>
+                                                     })}
+                                                 </tr>
+                                                     })}
+                                                     {for listed.iter().map(|(process, ended)| html!{
+                                                 <tr class={if *ended { "bg-red-100 dark:bg-red-900" } else { "bg-green-100 dark:bg-green-900" }}>
+                                                     {for columns.iter().enumerate().map(|(index, column)| match column {
+                                                         Column::Name => html!{
+                                                     <td class={classes!("px-2", "py-1", "text-base", "font-medium", "text-gray-800", "whitespace-nowrap", "dark:text-gray-300", ended.then_some("line-through"))}>{process.name.clone()}</td>
+                                                         },
+                                                         Column::Pid => html!{
+                                                     <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">{process.pid}</td>
+                                                         },
+                                                         _ => html!{
+                                                     <td class="px-2 py-1 text-base font-medium text-gray-800 whitespace-nowrap dark:text-gray-300">if Some(index) == label_column {{if *ended { "ended" } else { "started" }}}</td>
+                                                         },
+                                                     })}
                                                  </tr>
                                                      })}
                                                  </tbody>
@@ -189,6 +552,9 @@ pub fn processes() -> Html {
                              </div>
                              <div class="sticky bottom-0 right-0 items-center w-full p-4 bg-gray-100 border-t border-gray-200 sm:flex sm:justify-between dark:bg-gray-800 dark:border-gray-700">
                          </div>
+        if let Some(process) = selected {
+            <ProcessDetails {process} on_close={on_close_details} />
+        }
     </>
     }
 }
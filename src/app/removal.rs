@@ -0,0 +1,193 @@
+use super::try_invoke;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UnmountArgs {
+    mount_point: String,
+}
+
+#[derive(Serialize)]
+struct EjectArgs {
+    device: String,
+}
+
+#[derive(Serialize)]
+struct SignalArgs {
+    id: usize,
+    signal: &'static str,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct BlockingProcess {
+    pid: u32,
+    name: String,
+    paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum UnmountError {
+    NotRemovable,
+    NotMounted,
+    Busy { processes: Vec<BlockingProcess> },
+    Failed { message: String },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Removal {
+    Unmount,
+    Eject,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RemovalActionsProps {
+    pub directory: AttrValue,
+    pub device: AttrValue,
+    pub on_done: Callback<()>,
+}
+
+// unmount / eject buttons for a removable drive, listing whoever keeps it busy
+#[function_component(RemovalActions)]
+pub fn removal_actions(props: &RemovalActionsProps) -> Html {
+    let blocked = use_state(|| None::<(Removal, Vec<BlockingProcess>)>);
+    let message = use_state(|| None::<String>);
+
+    let remove = {
+        let blocked = blocked.clone();
+        let message = message.clone();
+        let directory = props.directory.to_string();
+        let device = props.device.to_string();
+        let on_done = props.on_done.clone();
+        Callback::from(move |removal: Removal| {
+            let (command, args, target) = match removal {
+                Removal::Unmount => (
+                    "unmount",
+                    to_value(&UnmountArgs {
+                        mount_point: directory.clone(),
+                    }),
+                    directory.clone(),
+                ),
+                Removal::Eject => (
+                    "eject",
+                    to_value(&EjectArgs {
+                        device: device.clone(),
+                    }),
+                    device.clone(),
+                ),
+            };
+            let blocked = blocked.clone();
+            let message = message.clone();
+            let on_done = on_done.clone();
+            spawn_local(async move {
+                let result = try_invoke(command, args.unwrap_or(JsValue::NULL)).await;
+                let text = match result {
+                    Ok(_) => {
+                        blocked.set(None);
+                        on_done.emit(());
+                        format!("{} can be removed safely", target)
+                    }
+                    Err(error) => match from_value::<UnmountError>(error) {
+                        Ok(UnmountError::Busy { processes }) => {
+                            blocked.set(Some((removal, processes)));
+                            format!("{} is in use", target)
+                        }
+                        Ok(UnmountError::NotRemovable) => {
+                            format!("{} is not a removable drive", target)
+                        }
+                        Ok(UnmountError::NotMounted) => format!("{} is not mounted", target),
+                        Ok(UnmountError::Failed { message }) => message,
+                        Err(_) => format!("could not remove {}", target),
+                    },
+                };
+                message.set(Some(text));
+            });
+        })
+    };
+
+    let send_signal = |pid: u32, signal: &'static str| {
+        Callback::from(move |_| {
+            spawn_local(async move {
+                let _ = try_invoke(
+                    "signal_process",
+                    to_value(&SignalArgs {
+                        id: pid as usize,
+                        signal,
+                    })
+                    .unwrap(),
+                )
+                .await;
+            });
+        })
+    };
+
+    let on_unmount = {
+        let remove = remove.clone();
+        Callback::from(move |_| remove.emit(Removal::Unmount))
+    };
+    let on_eject = {
+        let remove = remove.clone();
+        Callback::from(move |_| remove.emit(Removal::Eject))
+    };
+    let on_retry = {
+        let remove = remove.clone();
+        let removal = blocked.as_ref().map(|(removal, _)| *removal);
+        Callback::from(move |_| {
+            if let Some(removal) = removal {
+                remove.emit(removal);
+            }
+        })
+    };
+    let on_dismiss = {
+        let blocked = blocked.clone();
+        let message = message.clone();
+        Callback::from(move |_| {
+            blocked.set(None);
+            message.set(None);
+        })
+    };
+
+    let button_class = "text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-xs px-3 py-1 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700";
+
+    html! {
+    <>
+        <button onclick={on_unmount} class={button_class}>{"unmount"}</button>
+        <button onclick={on_eject} class={format!("ms-1 {}", button_class)}>{"eject"}</button>
+        if let Some(text) = &*message {
+            <div class="fixed inset-0 z-50 flex items-center justify-center bg-gray-900/50">
+                <div class="w-1/2 p-4 bg-white rounded-lg shadow whitespace-normal dark:bg-gray-800">
+                    <div class="mb-2 text-base font-semibold text-gray-800 dark:text-gray-300">{text.clone()}</div>
+                    if let Some((_, processes)) = &*blocked {
+                        <table class="min-w-full mb-2 divide-y divide-gray-200 dark:divide-gray-600">
+                            <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                {for processes.iter().map(|process| html!{
+                                <tr>
+                                    <td class="px-2 py-1 text-sm font-medium text-gray-800 dark:text-gray-300">{format!("{} ({})", process.name, process.pid)}</td>
+                                    <td class="px-2 py-1 text-xs text-gray-500 dark:text-gray-400">
+                                        {for process.paths.iter().map(|path| html!{ <div>{path.clone()}</div> })}
+                                    </td>
+                                    <td class="px-2 py-1 whitespace-nowrap">
+                                        <button onclick={send_signal(process.pid, "TERM")} class={button_class}>{"terminate"}</button>
+                                        <button onclick={send_signal(process.pid, "KILL")} class={format!("ms-1 {}", button_class)}>{"kill"}</button>
+                                    </td>
+                                </tr>
+                                })}
+                            </tbody>
+                        </table>
+                    }
+                    <div class="flex justify-end space-x-2">
+                        if blocked.is_some() {
+                            <button onclick={on_retry} class={button_class}>{"retry"}</button>
+                        }
+                        <button onclick={on_dismiss} class={button_class}>{"close"}</button>
+                    </div>
+                </div>
+            </div>
+        }
+    </>
+    }
+}
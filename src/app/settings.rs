@@ -0,0 +1,269 @@
+use super::columns::{Column, ColumnChooser, ALL};
+use super::exporter::{ExporterConfig, ExporterSettings};
+use super::hosts::is_remote;
+use super::{invoke, try_invoke};
+use crate::components::navbar::NavBar;
+use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::{from_value, to_value};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+// milliseconds, the backend keeps the interval within the first and the last
+pub(super) const REFRESH_INTERVALS: [u32; 6] = [500, 1000, 2000, 3000, 5000, 10000];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub(super) struct Sort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Sort {
+            column: Column::Name,
+            descending: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub(super) struct WindowSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+// kept by this machine whichever host is shown
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub(super) struct Settings {
+    pub version: u32,
+    pub refresh_interval: u32,
+    pub freeze_on_hover: bool,
+    pub sort: Sort,
+    pub filter: Option<String>,
+    pub window: Option<WindowSize>,
+    pub columns: Vec<Column>,
+    pub exporter: ExporterConfig,
+}
+
+// only the fields that are set are sent, the backend changes just those, so pages
+// saving different fields at the same time do not undo each other
+#[derive(Serialize, Default)]
+pub(super) struct Patch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freeze_on_hover: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Sort>,
+    // Some(None) clears the field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<Option<WindowSize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+}
+
+#[derive(Serialize)]
+struct Args {
+    patch: Patch,
+}
+
+pub(super) async fn load() -> Option<Settings> {
+    from_value(invoke("get_settings", JsValue::NULL).await).ok()
+}
+
+// answers with what the backend kept
+pub(super) async fn update(patch: Patch) -> Result<Settings, String> {
+    let args = to_value(&Args { patch }).unwrap();
+    let kept = try_invoke("update_settings", args)
+        .await
+        .map_err(|err| from_value::<String>(err).unwrap_or_else(|_| "could not save".into()))?;
+    from_value(kept).map_err(|_| "the settings could not be read".into())
+}
+
+pub(super) fn format_interval(millis: u32) -> String {
+    format!("{} s", millis as f64 / 1000.0)
+}
+
+#[function_component(SettingsPage)]
+pub fn settings_page() -> Html {
+    let settings = use_state(|| None::<Settings>);
+    let error = use_state(|| None::<String>);
+
+    use_effect_with((), {
+        let settings = settings.clone();
+        move |_| {
+            spawn_local(async move {
+                settings.set(load().await);
+            });
+        }
+    });
+
+    let save = {
+        let settings = settings.clone();
+        let error = error.clone();
+        Callback::from(move |patch: Patch| {
+            let settings = settings.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                match update(patch).await {
+                    Ok(saved) => {
+                        settings.set(Some(saved));
+                        error.set(None);
+                    }
+                    Err(message) => error.set(Some(message)),
+                }
+            });
+        })
+    };
+
+    let Some(current) = (*settings).clone() else {
+        return html! { <NavBar /> };
+    };
+
+    let on_interval = {
+        let save = save.clone();
+        Callback::from(move |e: Event| {
+            let Ok(millis) = e
+                .target_unchecked_into::<HtmlSelectElement>()
+                .value()
+                .parse()
+            else {
+                return;
+            };
+            save.emit(Patch {
+                refresh_interval: Some(millis),
+                ..Patch::default()
+            });
+        })
+    };
+    let on_freeze = {
+        let save = save.clone();
+        Callback::from(move |e: Event| {
+            let freeze = e.target_unchecked_into::<HtmlInputElement>().checked();
+            save.emit(Patch {
+                freeze_on_hover: Some(freeze),
+                ..Patch::default()
+            });
+        })
+    };
+    let sort = current.sort;
+    let on_sort_column = {
+        let save = save.clone();
+        Callback::from(move |e: Event| {
+            let index: usize = e
+                .target_unchecked_into::<HtmlSelectElement>()
+                .value()
+                .parse()
+                .unwrap_or(0);
+            let column = ALL[index.min(ALL.len() - 1)];
+            save.emit(Patch {
+                sort: Some(Sort { column, ..sort }),
+                ..Patch::default()
+            });
+        })
+    };
+    let on_descending = {
+        let save = save.clone();
+        Callback::from(move |e: Event| {
+            let descending = e.target_unchecked_into::<HtmlInputElement>().checked();
+            save.emit(Patch {
+                sort: Some(Sort { descending, ..sort }),
+                ..Patch::default()
+            });
+        })
+    };
+    let on_filter = {
+        let save = save.clone();
+        Callback::from(move |e: Event| {
+            let filter = e.target_unchecked_into::<HtmlInputElement>().value();
+            save.emit(Patch {
+                filter: Some(Some(filter).filter(|filter| !filter.is_empty())),
+                ..Patch::default()
+            });
+        })
+    };
+    let on_columns = {
+        let save = save.clone();
+        Callback::from(move |columns: Vec<Column>| {
+            save.emit(Patch {
+                columns: Some(columns),
+                ..Patch::default()
+            });
+        })
+    };
+    let on_forget_window = {
+        let save = save.clone();
+        Callback::from(move |_: MouseEvent| {
+            save.emit(Patch {
+                window: Some(None),
+                ..Patch::default()
+            });
+        })
+    };
+
+    let section_class = "px-4 py-3 flex flex-wrap items-center gap-2 border-b border-gray-200 text-sm text-gray-800 dark:border-gray-700 dark:text-gray-300";
+    let title_class = "w-40 font-medium";
+    let input_class = "p-2 text-sm bg-gray-50 border border-gray-300 rounded-lg dark:bg-gray-700 dark:border-gray-600 dark:text-white";
+    let button_class = "text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-sm px-4 py-2 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700";
+
+    html! {
+    <>
+        <NavBar />
+        <div class={section_class}>
+            <span class={title_class}>{"Refresh every"}</span>
+            <select onchange={on_interval} class={input_class}>
+                {for REFRESH_INTERVALS.iter().map(|millis| html! {
+                    <option value={millis.to_string()} selected={*millis == current.refresh_interval}>{format_interval(*millis)}</option>
+                })}
+            </select>
+            <label class="inline-flex items-center ms-4">
+                <input type="checkbox" checked={current.freeze_on_hover} onchange={on_freeze} class="w-4 h-4 me-2" />
+                {"Freeze the processes while hovering or with one selected"}
+            </label>
+        </div>
+        <div class={section_class}>
+            <span class={title_class}>{"Sort processes by"}</span>
+            <select onchange={on_sort_column} class={input_class}>
+                {for ALL.iter().enumerate().map(|(index, column)| html! {
+                    <option value={index.to_string()} selected={*column == current.sort.column}>{column.label()}</option>
+                })}
+            </select>
+            <label class="inline-flex items-center">
+                <input type="checkbox" checked={current.sort.descending} onchange={on_descending} class="w-4 h-4 me-2" />
+                {"Descending"}
+            </label>
+        </div>
+        <div class={section_class}>
+            <span class={title_class}>{"Process filter"}</span>
+            <input type="text" value={current.filter.clone().unwrap_or_default()} onchange={on_filter} placeholder="Show every process" class={classes!(input_class, "w-64")} />
+        </div>
+        <div class={section_class}>
+            <span class={title_class}>{"Process columns"}</span>
+            <span class="text-gray-500 dark:text-gray-400">
+                {current.columns.iter().map(Column::label).collect::<Vec<_>>().join(", ")}
+            </span>
+            <ColumnChooser columns={current.columns.clone()} on_change={on_columns} />
+        </div>
+        <div class={section_class}>
+            <span class={title_class}>{"Window size"}</span>
+            if let Some(size) = current.window {
+                {format!("{:.0} × {:.0}, restored at start", size.width, size.height)}
+                <button type="button" onclick={on_forget_window} class={button_class}>{"Forget"}</button>
+            } else {
+                <span class="text-gray-500 dark:text-gray-400">{"Remembered when the window is closed"}</span>
+            }
+        </div>
+        <ExporterSettings this_machine=true />
+        if is_remote() {
+            <ExporterSettings />
+        }
+        if let Some(message) = &*error {
+            <div class="px-4 py-2 text-sm text-red-600 dark:text-red-500">{message.clone()}</div>
+        }
+    </>
+    }
+}
@@ -0,0 +1,135 @@
+use super::invoke;
+use serde::Deserialize;
+use serde_wasm_bindgen::from_value;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+// step of the sliders, same as the backend record interval
+const STEP_MS: u64 = 5000;
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+struct TimeRange {
+    start: u64,
+    end: u64,
+}
+
+// `at` is None while showing live data, `diff_from` is set in diff mode
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct TimeSelection {
+    pub at: Option<u64>,
+    pub diff_from: Option<u64>,
+}
+
+pub fn format_timestamp(timestamp: u64) -> String {
+    js_sys::Date::new(&JsValue::from_f64(timestamp as f64))
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TimeTravelProps {
+    pub selection: TimeSelection,
+    pub on_change: Callback<TimeSelection>,
+}
+
+// toggles between live data and recorded history, with an optional second time to diff against
+#[function_component(TimeTravel)]
+pub fn time_travel(props: &TimeTravelProps) -> Html {
+    let bounds = use_state(|| None::<TimeRange>);
+
+    let selection = props.selection;
+    // the bounds are fetched again on every switch to history, which keeps growing
+    let on_toggle = {
+        let on_change = props.on_change.clone();
+        let bounds = bounds.clone();
+        Callback::from(move |_| match selection.at {
+            Some(_) => on_change.emit(TimeSelection::default()),
+            None => {
+                let on_change = on_change.clone();
+                let bounds = bounds.clone();
+                spawn_local(async move {
+                    let result = invoke("history_bounds", JsValue::NULL).await;
+                    let Ok(range) = from_value::<Option<TimeRange>>(result) else {
+                        return;
+                    };
+                    bounds.set(range);
+                    if let Some(range) = range {
+                        on_change.emit(TimeSelection {
+                            at: Some(range.end),
+                            diff_from: None,
+                        });
+                    }
+                });
+            }
+        })
+    };
+
+    let slider_value = |e: InputEvent| {
+        e.target_unchecked_into::<HtmlInputElement>()
+            .value()
+            .parse::<u64>()
+            .ok()
+    };
+    let on_at = {
+        let on_change = props.on_change.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(at) = slider_value(e) {
+                on_change.emit(TimeSelection {
+                    at: Some(at),
+                    ..selection
+                });
+            }
+        })
+    };
+    let on_diff_from = {
+        let on_change = props.on_change.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(from) = slider_value(e) {
+                on_change.emit(TimeSelection {
+                    diff_from: Some(from),
+                    ..selection
+                });
+            }
+        })
+    };
+    let on_diff_toggle = {
+        let on_change = props.on_change.clone();
+        let bounds = bounds.clone();
+        Callback::from(move |e: Event| {
+            let checked = e.target_unchecked_into::<HtmlInputElement>().checked();
+            on_change.emit(TimeSelection {
+                diff_from: bounds.filter(|_| checked).map(|range| range.start),
+                ..selection
+            });
+        })
+    };
+
+    let slider = |value: u64, oninput: Callback<InputEvent>| match *bounds {
+        Some(range) => html! {
+            <input type="range" class="w-64" min={range.start.to_string()} max={range.end.to_string()} step={STEP_MS.to_string()} value={value.to_string()} {oninput} />
+        },
+        None => html! {},
+    };
+
+    html! {
+        <div class="flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300">
+            <button onclick={on_toggle} class="text-gray-900 bg-white border border-gray-300 hover:bg-gray-100 font-medium rounded-lg text-sm px-3 py-2 dark:bg-gray-800 dark:text-white dark:border-gray-600 dark:hover:bg-gray-700">
+                {if selection.at.is_some() { "back to live" } else { "history" }}
+            </button>
+            if let Some(at) = selection.at {
+                if let Some(from) = selection.diff_from {
+                    {slider(from, on_diff_from)}
+                    <span class="whitespace-nowrap">{format_timestamp(from)}{" →"}</span>
+                }
+                {slider(at, on_at)}
+                <span class="whitespace-nowrap">{format_timestamp(at)}</span>
+                <label class="flex items-center whitespace-nowrap">
+                    <input type="checkbox" class="me-1" checked={selection.diff_from.is_some()} onchange={on_diff_toggle} />
+                    {"diff"}
+                </label>
+            }
+        </div>
+    }
+}
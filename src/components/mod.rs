@@ -1 +1,3 @@
 pub mod navbar;
+pub mod sparkline;
+pub mod treemap;
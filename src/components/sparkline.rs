@@ -0,0 +1,42 @@
+use yew::{function_component, html, AttrValue, Html, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct SparklineProps {
+    pub values: Vec<f64>,
+    #[prop_or(120)]
+    pub width: u32,
+    #[prop_or(24)]
+    pub height: u32,
+    #[prop_or(AttrValue::from("#2563eb"))]
+    pub color: AttrValue,
+    // fixed upper bound, otherwise scaled to the largest value
+    #[prop_or_default]
+    pub max: Option<f64>,
+}
+
+#[function_component(Sparkline)]
+pub fn sparkline(props: &SparklineProps) -> Html {
+    let width = props.width as f64;
+    let height = props.height as f64;
+    let max = props
+        .max
+        .unwrap_or_else(|| props.values.iter().cloned().fold(0.0, f64::max))
+        .max(f64::EPSILON);
+    let step = width / (props.values.len().max(2) - 1) as f64;
+    let points = props
+        .values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let y = height - (value / max).min(1.0) * (height - 1.0);
+            format!("{:.1},{:.1}", i as f64 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    html! {
+        <svg width={props.width.to_string()} height={props.height.to_string()} viewBox={format!("0 0 {} {}", props.width, props.height)}>
+            <polyline {points} fill="none" stroke={props.color.clone()} stroke-width="1.5" />
+        </svg>
+    }
+}
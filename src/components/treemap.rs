@@ -0,0 +1,135 @@
+use yew::{function_component, html, AttrValue, Callback, Html, Properties};
+
+#[derive(Clone, PartialEq)]
+pub struct TreemapItem {
+    pub key: AttrValue,
+    pub label: AttrValue,
+    pub value: f64,
+    pub highlight: bool,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TreemapProps {
+    pub items: Vec<TreemapItem>,
+    #[prop_or(800.0)]
+    pub width: f64,
+    #[prop_or(400.0)]
+    pub height: f64,
+    #[prop_or_default]
+    pub on_select: Callback<AttrValue>,
+}
+
+#[derive(Clone, Copy)]
+struct Rect {
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+}
+
+// aspect ratio of the worst rectangle when `row` is laid along a side of `length`
+fn worst(row: &[f64], length: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    let max = row.iter().cloned().fold(f64::MIN, f64::max);
+    let min = row.iter().cloned().fold(f64::MAX, f64::min);
+    let side = length * length;
+    f64::max(side * max / (sum * sum), (sum * sum) / (side * min))
+}
+
+fn layout_row(row: &[f64], bounds: &mut Rect, rects: &mut Vec<Rect>) {
+    let sum: f64 = row.iter().sum();
+    if bounds.w >= bounds.h {
+        let width = sum / bounds.h;
+        let mut y = bounds.y;
+        for area in row {
+            let height = area / width;
+            rects.push(Rect {
+                x: bounds.x,
+                y,
+                w: width,
+                h: height,
+            });
+            y += height;
+        }
+        bounds.x += width;
+        bounds.w -= width;
+    } else {
+        let height = sum / bounds.w;
+        let mut x = bounds.x;
+        for area in row {
+            let width = area / height;
+            rects.push(Rect {
+                x,
+                y: bounds.y,
+                w: width,
+                h: height,
+            });
+            x += width;
+        }
+        bounds.y += height;
+        bounds.h -= height;
+    }
+}
+
+// squarified treemap, values must be sorted largest first
+fn squarify(values: &[f64], mut bounds: Rect) -> Vec<Rect> {
+    let total: f64 = values.iter().sum();
+    let mut rects = Vec::with_capacity(values.len());
+    if total <= 0.0 {
+        return rects;
+    }
+    let scale = bounds.w * bounds.h / total;
+    let mut row: Vec<f64> = Vec::new();
+    for area in values.iter().map(|value| value * scale) {
+        let length = bounds.w.min(bounds.h);
+        let mut candidate = row.clone();
+        candidate.push(area);
+        if row.is_empty() || worst(&candidate, length) <= worst(&row, length) {
+            row = candidate;
+        } else {
+            layout_row(&row, &mut bounds, &mut rects);
+            row = vec![area];
+        }
+    }
+    if !row.is_empty() {
+        layout_row(&row, &mut bounds, &mut rects);
+    }
+    rects
+}
+
+#[function_component(Treemap)]
+pub fn treemap(props: &TreemapProps) -> Html {
+    let items: Vec<&TreemapItem> = props.items.iter().filter(|item| item.value > 0.0).collect();
+    let values: Vec<f64> = items.iter().map(|item| item.value).collect();
+    let rects = squarify(
+        &values,
+        Rect {
+            x: 0.0,
+            y: 0.0,
+            w: props.width,
+            h: props.height,
+        },
+    );
+
+    html! {
+        <svg width={props.width.to_string()} height={props.height.to_string()} viewBox={format!("0 0 {} {}", props.width, props.height)}>
+            {for items.iter().zip(rects).map(|(item, rect)| {
+                let onclick = {
+                    let on_select = props.on_select.clone();
+                    let key = item.key.clone();
+                    Callback::from(move |_| on_select.emit(key.clone()))
+                };
+                let fill = if item.highlight { "#93c5fd" } else { "#d1d5db" };
+                html! {
+                    <g {onclick} class="cursor-pointer">
+                        <title>{item.label.clone()}</title>
+                        <rect x={rect.x.to_string()} y={rect.y.to_string()} width={rect.w.to_string()} height={rect.h.to_string()} fill={fill} stroke="#ffffff" stroke-width="1" />
+                        if rect.w > 60.0 && rect.h > 14.0 {
+                            <text x={(rect.x + 4.0).to_string()} y={(rect.y + 12.0).to_string()} font-size="10" fill="#1f2937">{item.label.clone()}</text>
+                        }
+                    </g>
+                }
+            })}
+        </svg>
+    }
+}